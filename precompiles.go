@@ -8,19 +8,69 @@ package precompiles
 
 uint64_t __precompile_anemoi_gas(const void* data_ptr, const uint32_t data_len);
 uint8_t __precompile_anemoi(const void* data_ptr, const uint32_t data_len, void* ret_val);
+uint64_t __precompile_anemoi_with_domain_gas(const void* data_ptr, const uint32_t data_len);
+uint8_t __precompile_anemoi_with_domain(const void* data_ptr, const uint32_t data_len, const void* ret_val);
+uint64_t __precompile_anemoi_permutation_gas(const void* data_ptr, const uint32_t data_len);
+uint8_t __precompile_anemoi_permutation(const void* data_ptr, const uint32_t data_len, const void* ret_val);
+uint64_t __precompile_anemoi_xof_gas(const void* data_ptr, const uint32_t data_len);
+uint8_t __precompile_anemoi_xof(const void* data_ptr, const uint32_t data_len, const void* ret_val, const uint32_t ret_len);
+uint64_t __precompile_anemoi_bytes_gas(const void* data_ptr, const uint32_t data_len);
+uint8_t __precompile_anemoi_bytes(const void* data_ptr, const uint32_t data_len, const void* ret_val);
+uint64_t __precompile_anemoi_batch_gas(const void* data_ptr, const uint32_t data_len);
+uint8_t __precompile_anemoi_batch(const void* data_ptr, const uint32_t data_len, const void* ret_val, const uint32_t ret_len);
+
+uint64_t __precompile_merkle_append_gas(const void* data_ptr, const uint32_t data_len);
+uint8_t __precompile_merkle_append(const void* data_ptr, const uint32_t data_len, const void* ret_val, const uint32_t ret_len);
+uint64_t __precompile_smt_verify_gas(const void* data_ptr, const uint32_t data_len);
+uint8_t __precompile_smt_verify(const void* data_ptr, const uint32_t data_len);
+uint64_t __precompile_smt_update_gas(const void* data_ptr, const uint32_t data_len);
+uint8_t __precompile_smt_update(const void* data_ptr, const uint32_t data_len, const void* ret_val);
 
 uint64_t __precompile_ed_on_bn254_point_add_gas(const void* data_ptr, const uint32_t data_len);
 uint8_t __precompile_ed_on_bn254_point_add(const void* data_ptr, const uint32_t data_len, const void* ret_val);
+uint64_t __precompile_ed_on_bn254_point_neg_gas(const void* data_ptr, const uint32_t data_len);
+uint8_t __precompile_ed_on_bn254_point_neg(const void* data_ptr, const uint32_t data_len, const void* ret_val);
+uint64_t __precompile_ed_on_bn254_point_sub_gas(const void* data_ptr, const uint32_t data_len);
+uint8_t __precompile_ed_on_bn254_point_sub(const void* data_ptr, const uint32_t data_len, const void* ret_val);
+uint64_t __precompile_ed_on_bn254_point_double_gas(const void* data_ptr, const uint32_t data_len);
+uint8_t __precompile_ed_on_bn254_point_double(const void* data_ptr, const uint32_t data_len, const void* ret_val);
 uint64_t __precompile_ed_on_bn254_scalar_mul_gas(const void* data_ptr, const uint32_t data_len);
 uint8_t __precompile_ed_on_bn254_scalar_mul(const void* data_ptr, const uint32_t data_len, const void* ret_val);
+uint64_t __precompile_ed_on_bn254_generator_mul_gas(const void* data_ptr, const uint32_t data_len);
+uint8_t __precompile_ed_on_bn254_generator_mul(const void* data_ptr, const uint32_t data_len, const void* ret_val);
+uint64_t __precompile_ed_on_bn254_verify_signature_gas(const void* data_ptr, const uint32_t data_len);
+uint8_t __precompile_ed_on_bn254_verify_signature(const void* data_ptr, const uint32_t data_len);
+uint64_t __precompile_ed_on_bn254_batch_verify_signature_gas(const void* data_ptr, const uint32_t data_len);
+uint8_t __precompile_ed_on_bn254_batch_verify_signature(const void* data_ptr, const uint32_t data_len, const void* ret_val);
+
+uint64_t __precompile_elgamal_add_gas(const void* data_ptr, const uint32_t data_len);
+uint8_t __precompile_elgamal_add(const void* data_ptr, const uint32_t data_len, const void* ret_val);
+uint64_t __precompile_elgamal_scalar_mul_gas(const void* data_ptr, const uint32_t data_len);
+uint8_t __precompile_elgamal_scalar_mul(const void* data_ptr, const uint32_t data_len, const void* ret_val);
+uint64_t __precompile_elgamal_rerandomize_gas(const void* data_ptr, const uint32_t data_len);
+uint8_t __precompile_elgamal_rerandomize(const void* data_ptr, const uint32_t data_len, const void* ret_val);
+uint64_t __precompile_aggregate_keys_gas(const void* data_ptr, const uint32_t data_len);
+uint8_t __precompile_aggregate_keys(const void* data_ptr, const uint32_t data_len, const void* ret_val);
+uint64_t __precompile_unmask_gas(const void* data_ptr, const uint32_t data_len);
+uint8_t __precompile_unmask(const void* data_ptr, const uint32_t data_len, const void* ret_val);
 
 uint8_t __precompile_verify_matchmaking(const void* data_ptr, const uint32_t data_len);
 uint8_t __precompile_verify_shuffle(const void* data_ptr, const uint32_t data_len);
+uint8_t __precompile_verify_shuffle_detailed(const void* data_ptr, const uint32_t data_len, const void* ret_val);
+uint8_t __precompile_plonk_verify(const void* data_ptr, const uint32_t data_len);
+uint64_t __precompile_verify_matchmaking_with_seed_gas(const void* data_ptr, const uint32_t data_len);
+uint8_t __precompile_verify_matchmaking_with_seed(const void* data_ptr, const uint32_t data_len);
+uint8_t __precompile_verify_matchmaking_sized(const void* data_ptr, const uint32_t data_len);
 uint64_t __precompile_plonk_verify_gas(const void* data_ptr, const uint32_t data_len);
+uint64_t __precompile_plonk_verify_generic_gas(const void* data_ptr, const uint32_t data_len);
+uint64_t __precompile_plonk_verify_matchmaking_sized_gas(const void* data_ptr, const uint32_t data_len);
+uint64_t __precompile_poseidon_gas(const void* data_ptr, const uint32_t data_len);
+uint8_t __precompile_poseidon(const void* data_ptr, const uint32_t data_len, const void* ret_val);
 */
 import "C"
 import (
 	"errors"
+	"math/big"
 	"unsafe"
 
 	"github.com/ethereum/go-ethereum/accounts/abi"
@@ -36,6 +86,16 @@ func ErrHandle(code byte) error {
 		return errors.New("VerifyFail error")
 	} else if code == 4 {
 		return errors.New("input error")
+	} else if code == 5 {
+		return errors.New("unsupported version error")
+	} else if code == 6 {
+		return errors.New("length mismatch error")
+	} else if code == 7 {
+		return errors.New("unsupported size error")
+	} else if code == 8 {
+		return errors.New("params mismatch error")
+	} else if code == 9 {
+		return errors.New("invalid card error")
 	}
 	return nil
 }
@@ -140,6 +200,258 @@ func (a *EdOnBN254ScalarMul) RequiredGas(input []byte) uint64 {
 	return uint64(gas)
 }
 
+type EdOnBN254VerifySignature struct{}
+
+func (a *EdOnBN254VerifySignature) RegistryKey() common.Address {
+	return common.BytesToAddress([]byte{25})
+}
+
+func (a *EdOnBN254VerifySignature) RequiredGas(input []byte) uint64 {
+	if len(input) <= 0 {
+		return 0
+	}
+	cstr := unsafe.Pointer(&input[0])
+	len := C.uint(len(input))
+
+	gas := C.__precompile_ed_on_bn254_verify_signature_gas(cstr, len)
+
+	return uint64(gas)
+}
+
+func (a *EdOnBN254VerifySignature) Run(input []byte) ([]byte, error) {
+	if len(input) <= 0 {
+		return nil, ErrHandle(byte(4))
+	}
+
+	cstr := unsafe.Pointer(&input[0])
+	len := C.uint(len(input))
+
+	res := C.__precompile_ed_on_bn254_verify_signature(cstr, len)
+
+	boolType, _ := abi.NewType("bool", "", nil)
+
+	arguments := abi.Arguments{
+		{
+			Type: boolType,
+		},
+	}
+
+	data := true
+	if res != 0 {
+		data = false
+	}
+
+	encodedData, _ := arguments.Pack(data)
+
+	return encodedData, ErrHandle(byte(res))
+}
+
+type EdOnBN254BatchVerifySignature struct{}
+
+func (a *EdOnBN254BatchVerifySignature) RegistryKey() common.Address {
+	return common.BytesToAddress([]byte{26})
+}
+
+func (a *EdOnBN254BatchVerifySignature) RequiredGas(input []byte) uint64 {
+	if len(input) <= 0 {
+		return 0
+	}
+	cstr := unsafe.Pointer(&input[0])
+	len := C.uint(len(input))
+
+	gas := C.__precompile_ed_on_bn254_batch_verify_signature_gas(cstr, len)
+
+	return uint64(gas)
+}
+
+// Run returns an abi encoded bool, true when every signature verified.
+func (a *EdOnBN254BatchVerifySignature) Run(input []byte) ([]byte, error) {
+	if len(input) <= 0 {
+		return nil, ErrHandle(byte(4))
+	}
+
+	output := make([]byte, 32)
+	cout := unsafe.Pointer(&output[0])
+
+	cstr := unsafe.Pointer(&input[0])
+	len := C.uint(len(input))
+
+	res := C.__precompile_ed_on_bn254_batch_verify_signature(cstr, len, cout)
+
+	return output, ErrHandle(byte(res))
+}
+
+type ElGamalAdd struct{}
+
+func (a *ElGamalAdd) RegistryKey() common.Address {
+	return common.BytesToAddress([]byte{27})
+}
+
+func (a *ElGamalAdd) RequiredGas(input []byte) uint64 {
+	if len(input) <= 0 {
+		return 0
+	}
+	cstr := unsafe.Pointer(&input[0])
+	len := C.uint(len(input))
+
+	gas := C.__precompile_elgamal_add_gas(cstr, len)
+
+	return uint64(gas)
+}
+
+func (a *ElGamalAdd) Run(input []byte) ([]byte, error) {
+	if len(input) <= 0 {
+		return nil, ErrHandle(byte(4))
+	}
+
+	output := make([]byte, 128)
+	cout := unsafe.Pointer(&output[0])
+
+	cstr := unsafe.Pointer(&input[0])
+	len := C.uint(len(input))
+
+	res := C.__precompile_elgamal_add(cstr, len, cout)
+
+	return output, ErrHandle(byte(res))
+}
+
+type ElGamalScalarMul struct{}
+
+func (a *ElGamalScalarMul) RegistryKey() common.Address {
+	return common.BytesToAddress([]byte{28})
+}
+
+func (a *ElGamalScalarMul) RequiredGas(input []byte) uint64 {
+	if len(input) <= 0 {
+		return 0
+	}
+	cstr := unsafe.Pointer(&input[0])
+	len := C.uint(len(input))
+
+	gas := C.__precompile_elgamal_scalar_mul_gas(cstr, len)
+
+	return uint64(gas)
+}
+
+func (a *ElGamalScalarMul) Run(input []byte) ([]byte, error) {
+	if len(input) <= 0 {
+		return nil, ErrHandle(byte(4))
+	}
+
+	output := make([]byte, 128)
+	cout := unsafe.Pointer(&output[0])
+
+	cstr := unsafe.Pointer(&input[0])
+	len := C.uint(len(input))
+
+	res := C.__precompile_elgamal_scalar_mul(cstr, len, cout)
+
+	return output, ErrHandle(byte(res))
+}
+
+type ElGamalRerandomize struct{}
+
+func (a *ElGamalRerandomize) RegistryKey() common.Address {
+	return common.BytesToAddress([]byte{29})
+}
+
+func (a *ElGamalRerandomize) RequiredGas(input []byte) uint64 {
+	if len(input) <= 0 {
+		return 0
+	}
+	cstr := unsafe.Pointer(&input[0])
+	len := C.uint(len(input))
+
+	gas := C.__precompile_elgamal_rerandomize_gas(cstr, len)
+
+	return uint64(gas)
+}
+
+func (a *ElGamalRerandomize) Run(input []byte) ([]byte, error) {
+	if len(input) <= 0 {
+		return nil, ErrHandle(byte(4))
+	}
+
+	output := make([]byte, 128)
+	cout := unsafe.Pointer(&output[0])
+
+	cstr := unsafe.Pointer(&input[0])
+	len := C.uint(len(input))
+
+	res := C.__precompile_elgamal_rerandomize(cstr, len, cout)
+
+	return output, ErrHandle(byte(res))
+}
+
+type AggregateKeys struct{}
+
+func (a *AggregateKeys) RegistryKey() common.Address {
+	return common.BytesToAddress([]byte{30})
+}
+
+func (a *AggregateKeys) RequiredGas(input []byte) uint64 {
+	if len(input) <= 0 {
+		return 0
+	}
+	cstr := unsafe.Pointer(&input[0])
+	len := C.uint(len(input))
+
+	gas := C.__precompile_aggregate_keys_gas(cstr, len)
+
+	return uint64(gas)
+}
+
+func (a *AggregateKeys) Run(input []byte) ([]byte, error) {
+	if len(input) <= 0 {
+		return nil, ErrHandle(byte(4))
+	}
+
+	output := make([]byte, 64)
+	cout := unsafe.Pointer(&output[0])
+
+	cstr := unsafe.Pointer(&input[0])
+	len := C.uint(len(input))
+
+	res := C.__precompile_aggregate_keys(cstr, len, cout)
+
+	return output, ErrHandle(byte(res))
+}
+
+type Unmask struct{}
+
+func (a *Unmask) RegistryKey() common.Address {
+	return common.BytesToAddress([]byte{31})
+}
+
+func (a *Unmask) RequiredGas(input []byte) uint64 {
+	if len(input) <= 0 {
+		return 0
+	}
+	cstr := unsafe.Pointer(&input[0])
+	len := C.uint(len(input))
+
+	gas := C.__precompile_unmask_gas(cstr, len)
+
+	return uint64(gas)
+}
+
+// Run returns the card point followed by its index in the known card list.
+func (a *Unmask) Run(input []byte) ([]byte, error) {
+	if len(input) <= 0 {
+		return nil, ErrHandle(byte(4))
+	}
+
+	output := make([]byte, 96)
+	cout := unsafe.Pointer(&output[0])
+
+	cstr := unsafe.Pointer(&input[0])
+	len := C.uint(len(input))
+
+	res := C.__precompile_unmask(cstr, len, cout)
+
+	return output, ErrHandle(byte(res))
+}
+
 type VerifyMatchmaking struct{}
 
 func (m *VerifyMatchmaking) RegistryKey() common.Address {
@@ -231,3 +543,660 @@ func (s *VerifyShuffle) Run(input []byte) ([]byte, error) {
 
 	return encodedData, ErrHandle(byte(res))
 }
+
+type VerifyShuffleDetailed struct{}
+
+func (s *VerifyShuffleDetailed) RegistryKey() common.Address {
+	return common.BytesToAddress([]byte{50})
+}
+
+func (s *VerifyShuffleDetailed) RequiredGas(input []byte) uint64 {
+	if len(input) <= 0 {
+		return 0
+	}
+	cstr := unsafe.Pointer(&input[0])
+	len := C.uint(len(input))
+
+	gas := C.__precompile_plonk_verify_gas(cstr, len)
+
+	return uint64(gas)
+}
+
+// Run returns abi encoded (uint256 code, uint256 index) without reverting, so the
+// caller can read which card is malformed when code is 9.
+func (s *VerifyShuffleDetailed) Run(input []byte) ([]byte, error) {
+	if len(input) <= 0 {
+		return nil, ErrHandle(byte(4))
+	}
+
+	cstr := unsafe.Pointer(&input[0])
+	len := C.uint(len(input))
+
+	output := make([]byte, 64)
+	cout := unsafe.Pointer(&output[0])
+
+	C.__precompile_verify_shuffle_detailed(cstr, len, cout)
+
+	return output, nil
+}
+
+type PlonkVerify struct{}
+
+func (p *PlonkVerify) RegistryKey() common.Address {
+	return common.BytesToAddress([]byte{32})
+}
+
+func (p *PlonkVerify) RequiredGas(input []byte) uint64 {
+	if len(input) <= 0 {
+		return 0
+	}
+	cstr := unsafe.Pointer(&input[0])
+	len := C.uint(len(input))
+
+	gas := C.__precompile_plonk_verify_generic_gas(cstr, len)
+
+	return uint64(gas)
+}
+
+func (p *PlonkVerify) Run(input []byte) ([]byte, error) {
+	if len(input) <= 0 {
+		return nil, ErrHandle(byte(4))
+	}
+
+	cstr := unsafe.Pointer(&input[0])
+	len := C.uint(len(input))
+
+	res := C.__precompile_plonk_verify(cstr, len)
+
+	boolType, _ := abi.NewType("bool", "", nil)
+
+	arguments := abi.Arguments{
+		{
+			Type: boolType,
+		},
+	}
+
+	data := true
+	if res != 0 {
+		data = false
+	}
+
+	encodedData, _ := arguments.Pack(data)
+
+	return encodedData, ErrHandle(byte(res))
+}
+
+type VerifyMatchmakingWithSeed struct{}
+
+func (m *VerifyMatchmakingWithSeed) RegistryKey() common.Address {
+	return common.BytesToAddress([]byte{34})
+}
+
+func (m *VerifyMatchmakingWithSeed) RequiredGas(input []byte) uint64 {
+	if len(input) <= 0 {
+		return 0
+	}
+	cstr := unsafe.Pointer(&input[0])
+	len := C.uint(len(input))
+
+	gas := C.__precompile_verify_matchmaking_with_seed_gas(cstr, len)
+
+	return uint64(gas)
+}
+
+func (m *VerifyMatchmakingWithSeed) Run(input []byte) ([]byte, error) {
+	if len(input) <= 0 {
+		return nil, ErrHandle(byte(4))
+	}
+
+	cstr := unsafe.Pointer(&input[0])
+	len := C.uint(len(input))
+
+	res := C.__precompile_verify_matchmaking_with_seed(cstr, len)
+
+	boolType, _ := abi.NewType("bool", "", nil)
+
+	arguments := abi.Arguments{
+		{
+			Type: boolType,
+		},
+	}
+
+	data := true
+	if res != 0 {
+		data = false
+	}
+
+	encodedData, _ := arguments.Pack(data)
+
+	return encodedData, ErrHandle(byte(res))
+}
+
+type VerifyMatchmakingSized struct{}
+
+func (m *VerifyMatchmakingSized) RegistryKey() common.Address {
+	return common.BytesToAddress([]byte{35})
+}
+
+func (m *VerifyMatchmakingSized) RequiredGas(input []byte) uint64 {
+	if len(input) <= 0 {
+		return 0
+	}
+	cstr := unsafe.Pointer(&input[0])
+	len := C.uint(len(input))
+
+	gas := C.__precompile_plonk_verify_matchmaking_sized_gas(cstr, len)
+
+	return uint64(gas)
+}
+
+func (m *VerifyMatchmakingSized) Run(input []byte) ([]byte, error) {
+	if len(input) <= 0 {
+		return nil, ErrHandle(byte(4))
+	}
+
+	cstr := unsafe.Pointer(&input[0])
+	len := C.uint(len(input))
+
+	res := C.__precompile_verify_matchmaking_sized(cstr, len)
+
+	boolType, _ := abi.NewType("bool", "", nil)
+
+	arguments := abi.Arguments{
+		{
+			Type: boolType,
+		},
+	}
+
+	data := true
+	if res != 0 {
+		data = false
+	}
+
+	encodedData, _ := arguments.Pack(data)
+
+	return encodedData, ErrHandle(byte(res))
+}
+
+type AnemoiWithDomain struct{}
+
+func (a *AnemoiWithDomain) RegistryKey() common.Address {
+	return common.BytesToAddress([]byte{36})
+}
+
+func (a *AnemoiWithDomain) RequiredGas(input []byte) uint64 {
+	if len(input) <= 0 {
+		return 0
+	}
+	cstr := unsafe.Pointer(&input[0])
+	len := C.uint(len(input))
+
+	gas := C.__precompile_anemoi_with_domain_gas(cstr, len)
+
+	return uint64(gas)
+}
+
+func (a *AnemoiWithDomain) Run(input []byte) ([]byte, error) {
+	if len(input) <= 0 {
+		return nil, ErrHandle(byte(4))
+	}
+
+	output := make([]byte, 32)
+	cout := unsafe.Pointer(&output[0])
+
+	cstr := unsafe.Pointer(&input[0])
+	len := C.uint(len(input))
+
+	res := C.__precompile_anemoi_with_domain(cstr, len, cout)
+
+	return output, ErrHandle(byte(res))
+}
+
+type AnemoiPermutation struct{}
+
+func (a *AnemoiPermutation) RegistryKey() common.Address {
+	return common.BytesToAddress([]byte{37})
+}
+
+func (a *AnemoiPermutation) RequiredGas(input []byte) uint64 {
+	if len(input) <= 0 {
+		return 0
+	}
+	cstr := unsafe.Pointer(&input[0])
+	len := C.uint(len(input))
+
+	gas := C.__precompile_anemoi_permutation_gas(cstr, len)
+
+	return uint64(gas)
+}
+
+func (a *AnemoiPermutation) Run(input []byte) ([]byte, error) {
+	if len(input) <= 0 {
+		return nil, ErrHandle(byte(4))
+	}
+
+	output := make([]byte, 128)
+	cout := unsafe.Pointer(&output[0])
+
+	cstr := unsafe.Pointer(&input[0])
+	len := C.uint(len(input))
+
+	res := C.__precompile_anemoi_permutation(cstr, len, cout)
+
+	return output, ErrHandle(byte(res))
+}
+
+type AnemoiXof struct{}
+
+func (a *AnemoiXof) RegistryKey() common.Address {
+	return common.BytesToAddress([]byte{38})
+}
+
+func (a *AnemoiXof) RequiredGas(input []byte) uint64 {
+	if len(input) <= 0 {
+		return 0
+	}
+	cstr := unsafe.Pointer(&input[0])
+	len := C.uint(len(input))
+
+	gas := C.__precompile_anemoi_xof_gas(cstr, len)
+
+	return uint64(gas)
+}
+
+// the output is an abi encoded bytes32[] holding the requested count of words
+func (a *AnemoiXof) Run(input []byte) ([]byte, error) {
+	if len(input) < 64 {
+		return nil, ErrHandle(byte(4))
+	}
+
+	count := new(big.Int).SetBytes(input[32:64])
+	if count.Sign() == 0 || count.Cmp(big.NewInt(256)) > 0 {
+		return nil, ErrHandle(byte(7))
+	}
+
+	output := make([]byte, 64+32*count.Uint64())
+	cout := unsafe.Pointer(&output[0])
+	clen := C.uint(len(output))
+
+	cstr := unsafe.Pointer(&input[0])
+	len := C.uint(len(input))
+
+	res := C.__precompile_anemoi_xof(cstr, len, cout, clen)
+
+	return output, ErrHandle(byte(res))
+}
+
+type AnemoiBytes struct{}
+
+func (a *AnemoiBytes) RegistryKey() common.Address {
+	return common.BytesToAddress([]byte{39})
+}
+
+func (a *AnemoiBytes) RequiredGas(input []byte) uint64 {
+	if len(input) <= 0 {
+		return 0
+	}
+	cstr := unsafe.Pointer(&input[0])
+	len := C.uint(len(input))
+
+	gas := C.__precompile_anemoi_bytes_gas(cstr, len)
+
+	return uint64(gas)
+}
+
+func (a *AnemoiBytes) Run(input []byte) ([]byte, error) {
+	if len(input) <= 0 {
+		return nil, ErrHandle(byte(4))
+	}
+
+	output := make([]byte, 32)
+	cout := unsafe.Pointer(&output[0])
+
+	cstr := unsafe.Pointer(&input[0])
+	len := C.uint(len(input))
+
+	res := C.__precompile_anemoi_bytes(cstr, len, cout)
+
+	return output, ErrHandle(byte(res))
+}
+
+type AnemoiBatch struct{}
+
+func (a *AnemoiBatch) RegistryKey() common.Address {
+	return common.BytesToAddress([]byte{40})
+}
+
+func (a *AnemoiBatch) RequiredGas(input []byte) uint64 {
+	if len(input) <= 0 {
+		return 0
+	}
+	cstr := unsafe.Pointer(&input[0])
+	len := C.uint(len(input))
+
+	gas := C.__precompile_anemoi_batch_gas(cstr, len)
+
+	return uint64(gas)
+}
+
+// the output is an abi encoded bytes32[] with one digest per message
+func (a *AnemoiBatch) Run(input []byte) ([]byte, error) {
+	if len(input) < 64 {
+		return nil, ErrHandle(byte(4))
+	}
+
+	offset := new(big.Int).SetBytes(input[0:32])
+	if !offset.IsUint64() || offset.Uint64() > uint64(len(input))-32 {
+		return nil, ErrHandle(byte(2))
+	}
+	count := new(big.Int).SetBytes(input[offset.Uint64() : offset.Uint64()+32])
+	if count.Cmp(big.NewInt(1024)) > 0 {
+		return nil, ErrHandle(byte(7))
+	}
+
+	output := make([]byte, 64+32*count.Uint64())
+	cout := unsafe.Pointer(&output[0])
+	clen := C.uint(len(output))
+
+	cstr := unsafe.Pointer(&input[0])
+	len := C.uint(len(input))
+
+	res := C.__precompile_anemoi_batch(cstr, len, cout, clen)
+
+	return output, ErrHandle(byte(res))
+}
+
+type MerkleAppend struct{}
+
+func (a *MerkleAppend) RegistryKey() common.Address {
+	return common.BytesToAddress([]byte{41})
+}
+
+func (a *MerkleAppend) RequiredGas(input []byte) uint64 {
+	if len(input) <= 0 {
+		return 0
+	}
+	cstr := unsafe.Pointer(&input[0])
+	len := C.uint(len(input))
+
+	gas := C.__precompile_merkle_append_gas(cstr, len)
+
+	return uint64(gas)
+}
+
+// the output is abi encoded (bytes32[] frontier, bytes32 root)
+func (a *MerkleAppend) Run(input []byte) ([]byte, error) {
+	if len(input) < 96 {
+		return nil, ErrHandle(byte(4))
+	}
+
+	offset := new(big.Int).SetBytes(input[0:32])
+	if !offset.IsUint64() || offset.Uint64() > uint64(len(input))-32 {
+		return nil, ErrHandle(byte(2))
+	}
+	depth := new(big.Int).SetBytes(input[offset.Uint64() : offset.Uint64()+32])
+	if depth.Sign() == 0 || depth.Cmp(big.NewInt(32)) > 0 {
+		return nil, ErrHandle(byte(7))
+	}
+
+	output := make([]byte, 96+32*depth.Uint64())
+	cout := unsafe.Pointer(&output[0])
+	clen := C.uint(len(output))
+
+	cstr := unsafe.Pointer(&input[0])
+	len := C.uint(len(input))
+
+	res := C.__precompile_merkle_append(cstr, len, cout, clen)
+
+	return output, ErrHandle(byte(res))
+}
+
+type SmtVerify struct{}
+
+func (a *SmtVerify) RegistryKey() common.Address {
+	return common.BytesToAddress([]byte{42})
+}
+
+func (a *SmtVerify) RequiredGas(input []byte) uint64 {
+	if len(input) <= 0 {
+		return 0
+	}
+	cstr := unsafe.Pointer(&input[0])
+	len := C.uint(len(input))
+
+	gas := C.__precompile_smt_verify_gas(cstr, len)
+
+	return uint64(gas)
+}
+
+func (a *SmtVerify) Run(input []byte) ([]byte, error) {
+	if len(input) <= 0 {
+		return nil, ErrHandle(byte(4))
+	}
+
+	cstr := unsafe.Pointer(&input[0])
+	len := C.uint(len(input))
+
+	res := C.__precompile_smt_verify(cstr, len)
+
+	boolType, _ := abi.NewType("bool", "", nil)
+
+	arguments := abi.Arguments{
+		{
+			Type: boolType,
+		},
+	}
+
+	data := true
+	if res != 0 {
+		data = false
+	}
+
+	encodedData, _ := arguments.Pack(data)
+
+	return encodedData, ErrHandle(byte(res))
+}
+
+type SmtUpdate struct{}
+
+func (a *SmtUpdate) RegistryKey() common.Address {
+	return common.BytesToAddress([]byte{43})
+}
+
+func (a *SmtUpdate) RequiredGas(input []byte) uint64 {
+	if len(input) <= 0 {
+		return 0
+	}
+	cstr := unsafe.Pointer(&input[0])
+	len := C.uint(len(input))
+
+	gas := C.__precompile_smt_update_gas(cstr, len)
+
+	return uint64(gas)
+}
+
+func (a *SmtUpdate) Run(input []byte) ([]byte, error) {
+	if len(input) <= 0 {
+		return nil, ErrHandle(byte(4))
+	}
+
+	output := make([]byte, 32)
+	cout := unsafe.Pointer(&output[0])
+
+	cstr := unsafe.Pointer(&input[0])
+	len := C.uint(len(input))
+
+	res := C.__precompile_smt_update(cstr, len, cout)
+
+	return output, ErrHandle(byte(res))
+}
+
+type Poseidon struct{}
+
+func (a *Poseidon) RegistryKey() common.Address {
+	return common.BytesToAddress([]byte{44})
+}
+
+func (a *Poseidon) RequiredGas(input []byte) uint64 {
+	if len(input) <= 0 {
+		return 0
+	}
+	cstr := unsafe.Pointer(&input[0])
+	len := C.uint(len(input))
+
+	gas := C.__precompile_poseidon_gas(cstr, len)
+
+	return uint64(gas)
+}
+
+func (a *Poseidon) Run(input []byte) ([]byte, error) {
+	if len(input) <= 0 {
+		return nil, ErrHandle(byte(4))
+	}
+
+	output := make([]byte, 32)
+	cout := unsafe.Pointer(&output[0])
+
+	cstr := unsafe.Pointer(&input[0])
+	len := C.uint(len(input))
+
+	res := C.__precompile_poseidon(cstr, len, cout)
+
+	return output, ErrHandle(byte(res))
+}
+
+type EdOnBN254GeneratorMul struct{}
+
+func (a *EdOnBN254GeneratorMul) RegistryKey() common.Address {
+	return common.BytesToAddress([]byte{46})
+}
+
+func (a *EdOnBN254GeneratorMul) RequiredGas(input []byte) uint64 {
+	if len(input) <= 0 {
+		return 0
+	}
+	cstr := unsafe.Pointer(&input[0])
+	len := C.uint(len(input))
+
+	gas := C.__precompile_ed_on_bn254_generator_mul_gas(cstr, len)
+
+	return uint64(gas)
+}
+
+func (a *EdOnBN254GeneratorMul) Run(input []byte) ([]byte, error) {
+	if len(input) <= 0 {
+		return nil, ErrHandle(byte(4))
+	}
+
+	output := make([]byte, 64)
+	cout := unsafe.Pointer(&output[0])
+
+	cstr := unsafe.Pointer(&input[0])
+	len := C.uint(len(input))
+
+	res := C.__precompile_ed_on_bn254_generator_mul(cstr, len, cout)
+
+	return output, ErrHandle(byte(res))
+}
+
+type EdOnBN254PointNeg struct{}
+
+func (a *EdOnBN254PointNeg) RegistryKey() common.Address {
+	return common.BytesToAddress([]byte{47})
+}
+
+func (a *EdOnBN254PointNeg) RequiredGas(input []byte) uint64 {
+	if len(input) <= 0 {
+		return 0
+	}
+	cstr := unsafe.Pointer(&input[0])
+	len := C.uint(len(input))
+
+	gas := C.__precompile_ed_on_bn254_point_neg_gas(cstr, len)
+
+	return uint64(gas)
+}
+
+func (a *EdOnBN254PointNeg) Run(input []byte) ([]byte, error) {
+	if len(input) <= 0 {
+		return nil, ErrHandle(byte(4))
+	}
+
+	output := make([]byte, 64)
+	cout := unsafe.Pointer(&output[0])
+
+	cstr := unsafe.Pointer(&input[0])
+	len := C.uint(len(input))
+
+	res := C.__precompile_ed_on_bn254_point_neg(cstr, len, cout)
+
+	return output, ErrHandle(byte(res))
+}
+
+type EdOnBN254PointSub struct{}
+
+func (a *EdOnBN254PointSub) RegistryKey() common.Address {
+	return common.BytesToAddress([]byte{48})
+}
+
+func (a *EdOnBN254PointSub) RequiredGas(input []byte) uint64 {
+	if len(input) <= 0 {
+		return 0
+	}
+	cstr := unsafe.Pointer(&input[0])
+	len := C.uint(len(input))
+
+	gas := C.__precompile_ed_on_bn254_point_sub_gas(cstr, len)
+
+	return uint64(gas)
+}
+
+func (a *EdOnBN254PointSub) Run(input []byte) ([]byte, error) {
+	if len(input) <= 0 {
+		return nil, ErrHandle(byte(4))
+	}
+
+	output := make([]byte, 64)
+	cout := unsafe.Pointer(&output[0])
+
+	cstr := unsafe.Pointer(&input[0])
+	len := C.uint(len(input))
+
+	res := C.__precompile_ed_on_bn254_point_sub(cstr, len, cout)
+
+	return output, ErrHandle(byte(res))
+}
+
+type EdOnBN254PointDouble struct{}
+
+func (a *EdOnBN254PointDouble) RegistryKey() common.Address {
+	return common.BytesToAddress([]byte{49})
+}
+
+func (a *EdOnBN254PointDouble) RequiredGas(input []byte) uint64 {
+	if len(input) <= 0 {
+		return 0
+	}
+	cstr := unsafe.Pointer(&input[0])
+	len := C.uint(len(input))
+
+	gas := C.__precompile_ed_on_bn254_point_double_gas(cstr, len)
+
+	return uint64(gas)
+}
+
+func (a *EdOnBN254PointDouble) Run(input []byte) ([]byte, error) {
+	if len(input) <= 0 {
+		return nil, ErrHandle(byte(4))
+	}
+
+	output := make([]byte, 64)
+	cout := unsafe.Pointer(&output[0])
+
+	cstr := unsafe.Pointer(&input[0])
+	len := C.uint(len(input))
+
+	res := C.__precompile_ed_on_bn254_point_double(cstr, len, cout)
+
+	return output, ErrHandle(byte(res))
+}
@@ -53,3 +53,24 @@ func TestVerifyShuffle(t *testing.T) {
 	//assert.Nil(t, err)
 	fmt.Println(common.Bytes2Hex(res))
 }
+
+func TestAnemoiBatchOffsetOverflow(t *testing.T) {
+	a := AnemoiBatch{}
+	// an offset of 2^64 - 16 wraps around when 32 is added to it
+	inputData := "000000000000000000000000000000000000000000000000fffffffffffffff0" +
+		"0000000000000000000000000000000000000000000000000000000000000001"
+	inputBytes := common.Hex2Bytes(inputData)
+	_, err := a.Run(inputBytes)
+	assert.NotNil(t, err)
+}
+
+func TestMerkleAppendOffsetOverflow(t *testing.T) {
+	a := MerkleAppend{}
+	// an offset of 2^64 - 16 wraps around when 32 is added to it
+	inputData := "000000000000000000000000000000000000000000000000fffffffffffffff0" +
+		"0000000000000000000000000000000000000000000000000000000000000000" +
+		"0000000000000000000000000000000000000000000000000000000000000001"
+	inputBytes := common.Hex2Bytes(inputData)
+	_, err := a.Run(inputBytes)
+	assert.NotNil(t, err)
+}
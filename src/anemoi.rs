@@ -4,13 +4,27 @@ use ark_bn254::Fr;
 use ark_ff::{BigInteger, PrimeField};
 use ark_std::panic;
 use core::slice;
-use ethabi::ParamType;
+use ethabi::{ParamType, Token};
+use primitive_types::U256;
 use uzkge::anemoi::{AnemoiJive, AnemoiJive254};
 
-use crate::{Error, Result};
+use crate::{utils, Error, Result};
 
 pub const ANEMOI_EVAL: u64 = 100;
 
+/// Number of columns of the `AnemoiJive254` state, the state is `x[N]` and `y[N]`.
+pub const ANEMOI_N: usize = 2;
+/// Number of rounds of `AnemoiJive254`.
+pub const ANEMOI_ROUNDS: usize = 14;
+/// Field elements absorbed or squeezed per permutation, `x[0..N]` and `y[0..N-1]`.
+pub const ANEMOI_RATE: usize = 2 * ANEMOI_N - 1;
+/// Max outputs of one `__precompile_anemoi_xof` call.
+pub const MAX_XOF_OUTPUTS: usize = 256;
+/// Max messages of one `__precompile_anemoi_batch` call.
+pub const MAX_BATCH_MESSAGES: usize = 1024;
+/// Bytes packed into one field element by `pack_bytes`, always below the modulus.
+pub const PACKED_CHUNK_SIZE: usize = 31;
+
 #[no_mangle]
 #[allow(clippy::not_unsafe_ptr_arg_deref)]
 pub extern "C" fn __precompile_anemoi(
@@ -41,26 +55,361 @@ pub extern "C" fn __precompile_anemoi_gas(data_ptr: *const u8, data_len: usize)
     data.len() as u64 / 32 * ANEMOI_EVAL
 }
 
+/// Decode a `bytes32[]` token into field elements, reducing each word modulo the field.
+pub(crate) fn into_field_array(tk: Option<Token>) -> Result<Vec<Fr>> {
+    let hs = tk.and_then(|v| v.into_array()).ok_or(Error::Deserialize)?;
+
+    let mut inputs: Vec<Fr> = Vec::with_capacity(hs.len());
+    for r in hs {
+        let h = r.into_fixed_bytes().ok_or(Error::Deserialize)?;
+        inputs.push(Fr::from_be_bytes_mod_order(&h));
+    }
+
+    Ok(inputs)
+}
+
 fn eval_variable_length_hash(data: &[u8], ret: &mut [u8]) -> Result<()> {
     let rs = ethabi::decode(
         &[ParamType::Array(Box::new(ParamType::FixedBytes(32)))],
         data,
     )
     .map_err(|_| Error::Deserialize)?;
+    let inputs = into_field_array(rs.first().cloned())?;
+
+    let res = AnemoiJive254::eval_variable_length_hash(&inputs);
+
+    ret.copy_from_slice(&res.into_bigint().to_bytes_be());
+
+    Ok(())
+}
+
+#[no_mangle]
+#[allow(clippy::not_unsafe_ptr_arg_deref)]
+pub extern "C" fn __precompile_anemoi_with_domain(
+    data_ptr: *const u8,
+    data_len: usize,
+    ret_val: *mut u8,
+) -> u8 {
+    let result = panic::catch_unwind(|| {
+        let data = unsafe { slice::from_raw_parts(data_ptr, data_len) };
+        let ret = unsafe { slice::from_raw_parts_mut(ret_val, 32) };
+        match eval_hash_with_domain(data, ret) {
+            Ok(()) => 0,
+            Err(e) => e.code(),
+        }
+    });
+    if let Ok(code) = result {
+        code
+    } else {
+        Error::Unknown.code()
+    }
+}
+
+#[no_mangle]
+#[allow(clippy::not_unsafe_ptr_arg_deref)]
+pub extern "C" fn __precompile_anemoi_with_domain_gas(data_ptr: *const u8, data_len: usize) -> u64 {
+    let data = unsafe { slice::from_raw_parts(data_ptr, data_len) };
+
+    data.len() as u64 / 32 * ANEMOI_EVAL
+}
+
+/// Hash `inputs` under the domain separation tag `domain`.
+///
+/// The tag is absorbed as the first element, so the digest equals
+/// `eval_variable_length_hash(&[domain, inputs..])` and a circuit reproduces it with
+/// `TurboCS::anemoi_variable_length_hash` over the tag and input variables. Callers
+/// that also hash untagged data must keep its first element out of the tag space.
+pub fn hash_with_domain(domain: Fr, inputs: &[Fr]) -> Fr {
+    let mut v = Vec::with_capacity(inputs.len() + 1);
+    v.push(domain);
+    v.extend_from_slice(inputs);
+
+    AnemoiJive254::eval_variable_length_hash(&v)
+}
+
+fn eval_hash_with_domain(data: &[u8], ret: &mut [u8]) -> Result<()> {
+    let rs = ethabi::decode(
+        &[
+            ParamType::Uint(256),
+            ParamType::Array(Box::new(ParamType::FixedBytes(32))),
+        ],
+        data,
+    )
+    .map_err(|_| Error::Deserialize)?;
+    let domain = utils::into_uint256(rs.first().cloned()).ok_or(Error::Deserialize)?;
+    // a tag must not alias another one modulo the field
+    let domain = utils::into_field(domain)?;
+    let inputs = into_field_array(rs.get(1).cloned())?;
+
+    let res = hash_with_domain(domain, &inputs);
+
+    ret.copy_from_slice(&res.into_bigint().to_bytes_be());
+
+    Ok(())
+}
+
+#[no_mangle]
+#[allow(clippy::not_unsafe_ptr_arg_deref)]
+pub extern "C" fn __precompile_anemoi_permutation(
+    data_ptr: *const u8,
+    data_len: usize,
+    ret_val: *mut u8,
+) -> u8 {
+    let result = panic::catch_unwind(|| {
+        let data = unsafe { slice::from_raw_parts(data_ptr, data_len) };
+        let ret = unsafe { slice::from_raw_parts_mut(ret_val, 2 * ANEMOI_N * 32) };
+        match eval_permutation(data, ret) {
+            Ok(()) => 0,
+            Err(e) => e.code(),
+        }
+    });
+    if let Ok(code) = result {
+        code
+    } else {
+        Error::Unknown.code()
+    }
+}
+
+#[no_mangle]
+#[allow(clippy::not_unsafe_ptr_arg_deref)]
+pub extern "C" fn __precompile_anemoi_permutation_gas(_: *const u8, _: usize) -> u64 {
+    ANEMOI_EVAL
+}
+
+fn eval_permutation(data: &[u8], ret: &mut [u8]) -> Result<()> {
+    let state = ParamType::Array(Box::new(ParamType::FixedBytes(32)));
+    let rs = ethabi::decode(&[state.clone(), state], data).map_err(|_| Error::Deserialize)?;
+    let x = into_field_array(rs.first().cloned())?;
+    let y = into_field_array(rs.get(1).cloned())?;
+
+    let mut x: [Fr; ANEMOI_N] = x.try_into().map_err(|_| Error::LengthMismatch)?;
+    let mut y: [Fr; ANEMOI_N] = y.try_into().map_err(|_| Error::LengthMismatch)?;
+
+    AnemoiJive254::anemoi_permutation(&mut x, &mut y);
+
+    for (i, v) in x.iter().chain(y.iter()).enumerate() {
+        ret[i * 32..(i + 1) * 32].copy_from_slice(&v.into_bigint().to_bytes_be());
+    }
+
+    Ok(())
+}
+
+#[no_mangle]
+#[allow(clippy::not_unsafe_ptr_arg_deref)]
+pub extern "C" fn __precompile_anemoi_xof(
+    data_ptr: *const u8,
+    data_len: usize,
+    ret_val: *mut u8,
+    ret_len: usize,
+) -> u8 {
+    let result = panic::catch_unwind(|| {
+        let data = unsafe { slice::from_raw_parts(data_ptr, data_len) };
+        let ret = unsafe { slice::from_raw_parts_mut(ret_val, ret_len) };
+        match eval_xof(data, ret) {
+            Ok(()) => 0,
+            Err(e) => e.code(),
+        }
+    });
+    if let Ok(code) = result {
+        code
+    } else {
+        Error::Unknown.code()
+    }
+}
+
+#[no_mangle]
+#[allow(clippy::not_unsafe_ptr_arg_deref)]
+pub extern "C" fn __precompile_anemoi_xof_gas(data_ptr: *const u8, data_len: usize) -> u64 {
+    let data = unsafe { slice::from_raw_parts(data_ptr, data_len) };
+
+    // the output count is the second head word, one more permutation per extra block
+    let count = data
+        .get(32..64)
+        .map(U256::from_big_endian)
+        .filter(|c| *c <= U256::from(MAX_XOF_OUTPUTS))
+        .map(|c| c.as_usize())
+        .unwrap_or(0);
+    let squeezes = count.saturating_sub(1) / ANEMOI_RATE;
+
+    data.len() as u64 / 32 * ANEMOI_EVAL + squeezes as u64 * ANEMOI_EVAL
+}
+
+/// Byte length of the ABI encoded `bytes32[]` returned for `count` words.
+pub fn bytes32_array_len(count: usize) -> usize {
+    64 + count * 32
+}
+
+fn eval_xof(data: &[u8], ret: &mut [u8]) -> Result<()> {
+    let rs = ethabi::decode(
+        &[
+            ParamType::Array(Box::new(ParamType::FixedBytes(32))),
+            ParamType::Uint(256),
+        ],
+        data,
+    )
+    .map_err(|_| Error::Deserialize)?;
+    let inputs = into_field_array(rs.first().cloned())?;
+    let count = utils::into_uint256(rs.get(1).cloned()).ok_or(Error::Deserialize)?;
+
+    if count.is_zero() || count > U256::from(MAX_XOF_OUTPUTS) {
+        return Err(Error::UnsupportedSize);
+    }
+    let count = count.as_usize();
+    if ret.len() != bytes32_array_len(count) {
+        return Err(Error::LengthMismatch);
+    }
+
+    let outputs = AnemoiJive254::eval_stream_cipher(&inputs, count)
+        .iter()
+        .map(|f| Token::FixedBytes(f.into_bigint().to_bytes_be()))
+        .collect();
+
+    ret.copy_from_slice(&ethabi::encode(&[Token::Array(outputs)]));
+
+    Ok(())
+}
+
+#[no_mangle]
+#[allow(clippy::not_unsafe_ptr_arg_deref)]
+pub extern "C" fn __precompile_anemoi_bytes(
+    data_ptr: *const u8,
+    data_len: usize,
+    ret_val: *mut u8,
+) -> u8 {
+    let result = panic::catch_unwind(|| {
+        let data = unsafe { slice::from_raw_parts(data_ptr, data_len) };
+        let ret = unsafe { slice::from_raw_parts_mut(ret_val, 32) };
+        match eval_hash_bytes(data, ret) {
+            Ok(()) => 0,
+            Err(e) => e.code(),
+        }
+    });
+    if let Ok(code) = result {
+        code
+    } else {
+        Error::Unknown.code()
+    }
+}
+
+#[no_mangle]
+#[allow(clippy::not_unsafe_ptr_arg_deref)]
+pub extern "C" fn __precompile_anemoi_bytes_gas(data_ptr: *const u8, data_len: usize) -> u64 {
+    let data = unsafe { slice::from_raw_parts(data_ptr, data_len) };
+
+    // one element per 31 bytes plus the length element
+    let elements = data.len().div_ceil(PACKED_CHUNK_SIZE) + 1;
+
+    elements as u64 * ANEMOI_EVAL
+}
+
+/// Pack a byte string into field elements.
+///
+/// The message is split into 31-byte chunks, the last one right-padded with
+/// zeros, each read as a big-endian integer. The byte length of the message is
+/// appended as the final element, which keeps the packing injective.
+pub fn pack_bytes(msg: &[u8]) -> Vec<Fr> {
+    let mut v = Vec::with_capacity(msg.len().div_ceil(PACKED_CHUNK_SIZE) + 1);
+    for chunk in msg.chunks(PACKED_CHUNK_SIZE) {
+        let mut buf = [0u8; PACKED_CHUNK_SIZE];
+        buf[..chunk.len()].copy_from_slice(chunk);
+        v.push(Fr::from_be_bytes_mod_order(&buf));
+    }
+    v.push(Fr::from(msg.len() as u64));
+
+    v
+}
+
+/// Hash a byte string with the packing of `pack_bytes`.
+pub fn hash_bytes(msg: &[u8]) -> Fr {
+    AnemoiJive254::eval_variable_length_hash(&pack_bytes(msg))
+}
+
+fn eval_hash_bytes(data: &[u8], ret: &mut [u8]) -> Result<()> {
+    let rs = ethabi::decode(&[ParamType::Bytes], data).map_err(|_| Error::Deserialize)?;
+    let msg = utils::into_bytes(rs.first().cloned()).ok_or(Error::Deserialize)?;
+
+    let res = hash_bytes(&msg);
+
+    ret.copy_from_slice(&res.into_bigint().to_bytes_be());
+
+    Ok(())
+}
+
+#[no_mangle]
+#[allow(clippy::not_unsafe_ptr_arg_deref)]
+pub extern "C" fn __precompile_anemoi_batch(
+    data_ptr: *const u8,
+    data_len: usize,
+    ret_val: *mut u8,
+    ret_len: usize,
+) -> u8 {
+    let result = panic::catch_unwind(|| {
+        let data = unsafe { slice::from_raw_parts(data_ptr, data_len) };
+        let ret = unsafe { slice::from_raw_parts_mut(ret_val, ret_len) };
+        match eval_batch_hash(data, ret) {
+            Ok(()) => 0,
+            Err(e) => e.code(),
+        }
+    });
+    if let Ok(code) = result {
+        code
+    } else {
+        Error::Unknown.code()
+    }
+}
+
+#[no_mangle]
+#[allow(clippy::not_unsafe_ptr_arg_deref)]
+pub extern "C" fn __precompile_anemoi_batch_gas(data_ptr: *const u8, data_len: usize) -> u64 {
+    let data = unsafe { slice::from_raw_parts(data_ptr, data_len) };
+
+    // summed per message, an empty message still costs one permutation
+    match decode_batch(data) {
+        Ok(messages) => messages
+            .iter()
+            .map(|m| m.len().max(1) as u64 * ANEMOI_EVAL)
+            .sum(),
+        Err(_) => data.len() as u64 / 32 * ANEMOI_EVAL,
+    }
+}
+
+fn decode_batch(data: &[u8]) -> Result<Vec<Vec<Fr>>> {
+    let rs = ethabi::decode(
+        &[ParamType::Array(Box::new(ParamType::Array(Box::new(
+            ParamType::FixedBytes(32),
+        ))))],
+        data,
+    )
+    .map_err(|_| Error::Deserialize)?;
     let hs = rs
         .first()
-        .and_then(|v| v.clone().into_array())
+        .cloned()
+        .and_then(|v| v.into_array())
         .ok_or(Error::Deserialize)?;
 
-    let mut inputs: Vec<Fr> = Vec::new();
-    for r in hs {
-        let h = r.into_fixed_bytes().ok_or(Error::Deserialize)?;
-        inputs.push(Fr::from_be_bytes_mod_order(&h));
+    hs.into_iter().map(|h| into_field_array(Some(h))).collect()
+}
+
+/// Hash every message independently, the digests keep the order of `messages`.
+pub fn batch_hash(messages: &[Vec<Fr>]) -> Vec<Fr> {
+    utils::par_map(messages, |m| AnemoiJive254::eval_variable_length_hash(m))
+}
+
+fn eval_batch_hash(data: &[u8], ret: &mut [u8]) -> Result<()> {
+    let messages = decode_batch(data)?;
+    if messages.len() > MAX_BATCH_MESSAGES {
+        return Err(Error::UnsupportedSize);
+    }
+    if ret.len() != bytes32_array_len(messages.len()) {
+        return Err(Error::LengthMismatch);
     }
 
-    let res = AnemoiJive254::eval_variable_length_hash(&inputs);
+    let digests = batch_hash(&messages)
+        .iter()
+        .map(|f| Token::FixedBytes(f.into_bigint().to_bytes_be()))
+        .collect();
 
-    ret.copy_from_slice(&res.into_bigint().to_bytes_be());
+    ret.copy_from_slice(&ethabi::encode(&[Token::Array(digests)]));
 
     Ok(())
 }
@@ -68,10 +417,10 @@ fn eval_variable_length_hash(data: &[u8], ret: &mut [u8]) -> Result<()> {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use ark_ff::{vec, UniformRand};
+    use ark_ff::{vec, Field, MontFp, One, UniformRand, Zero};
     use ark_std::rand::SeedableRng;
-    use ethabi::Token;
     use rand_chacha::ChaChaRng;
+    use uzkge::plonk::constraint_system::TurboCS;
 
     #[test]
     fn anemoi_works() {
@@ -103,4 +452,251 @@ mod tests {
 
         assert_eq!(r1, r2);
     }
+
+    #[test]
+    fn anemoi_with_domain_works() {
+        let mut prng = ChaChaRng::from_seed([0u8; 32]);
+        let f1 = Fr::rand(&mut prng);
+        let f2 = Fr::rand(&mut prng);
+        let domain = Fr::from(7u64);
+
+        let r1 = AnemoiJive254::eval_variable_length_hash(&[domain, f1, f2])
+            .into_bigint()
+            .to_bytes_be();
+
+        let data = ethabi::encode(&[
+            Token::Uint(7u64.into()),
+            Token::Array(vec![
+                Token::FixedBytes(f1.into_bigint().to_bytes_be()),
+                Token::FixedBytes(f2.into_bigint().to_bytes_be()),
+            ]),
+        ]);
+        let mut ret = vec![0u8; 32];
+        eval_hash_with_domain(&data, &mut ret).unwrap();
+        assert_eq!(r1, ret);
+
+        // an untagged hash of the same inputs differs
+        let untagged = AnemoiJive254::eval_variable_length_hash(&[f1, f2])
+            .into_bigint()
+            .to_bytes_be();
+        assert_ne!(untagged, ret);
+    }
+
+    #[test]
+    fn anemoi_with_domain_matches_gadget() {
+        let mut prng = ChaChaRng::from_seed([0u8; 32]);
+        let domain = Fr::from(7u64);
+        let inputs = [Fr::rand(&mut prng), Fr::rand(&mut prng)];
+        let digest = hash_with_domain(domain, &inputs);
+
+        // a circuit proves the tagged digest with the stock sponge gadget, the tag
+        // being its first input variable
+        let trace =
+            AnemoiJive254::eval_variable_length_hash_with_trace(&[domain, inputs[0], inputs[1]]);
+        let mut cs = TurboCS::new();
+        cs.load_anemoi_parameters::<AnemoiJive254>();
+        let vars = [domain, inputs[0], inputs[1]].map(|v| cs.new_variable(v));
+        let output = cs.new_variable(digest);
+        cs.anemoi_variable_length_hash::<AnemoiJive254>(&trace, &vars, output);
+
+        let witness = cs.get_and_clear_witness();
+        cs.verify_witness(&witness, &[]).unwrap();
+
+        let mut bad = witness.clone();
+        bad[output] += Fr::from(1u64);
+        assert!(cs.verify_witness(&bad, &[]).is_err());
+    }
+
+    #[test]
+    fn anemoi_permutation_matches_sponge() {
+        let mut prng = ChaChaRng::from_seed([0u8; 32]);
+        let x = [Fr::rand(&mut prng), Fr::rand(&mut prng)];
+        let y = [Fr::rand(&mut prng), Fr::zero()];
+
+        let encode = |v: &[Fr]| {
+            Token::Array(
+                v.iter()
+                    .map(|f| Token::FixedBytes(f.into_bigint().to_bytes_be()))
+                    .collect(),
+            )
+        };
+        let mut ret = vec![0u8; 128];
+        eval_permutation(&ethabi::encode(&[encode(&x), encode(&y)]), &mut ret).unwrap();
+
+        // a full-rate block `[a, b, c]` is absorbed as `x = [a, b], y = [c, 0]` and
+        // permuted once, so the sponge digest is the first element of the permuted state
+        let digest = AnemoiJive254::eval_variable_length_hash(&[x[0], x[1], y[0]]);
+        assert_eq!(Fr::from_be_bytes_mod_order(&ret[..32]), digest);
+
+        // the state must have exactly N elements per side
+        let data = ethabi::encode(&[encode(&[x[0], x[1], y[0]]), encode(&y)]);
+        assert!(matches!(
+            eval_permutation(&data, &mut ret),
+            Err(Error::LengthMismatch)
+        ));
+    }
+
+    #[test]
+    fn anemoi_permutation_reference_vector() {
+        type P = AnemoiJive254;
+
+        // round keys of the Anemoi specification, from the first and second hundred
+        // decimals of pi: C[i][j] = g * pi_0^2i + (pi_0^i + pi_1^j)^5 and
+        // D[i][j] = g * pi_1^2j + (pi_0^i + pi_1^j)^5 + g^-1
+        let decimal = |digits: &str| {
+            digits.bytes().fold(Fr::zero(), |acc, d| {
+                acc * Fr::from(10u64) + Fr::from(d - b'0')
+            })
+        };
+        let pi_0 = decimal("1415926535897932384626433832795028841971693993751058209749445923078164062862089986280348253421170679");
+        let pi_1 = decimal("8214808651328230664709384460955058223172535940812848111745028410270193852110555964462294895493038196");
+        let g = <P as AnemoiJive<Fr, ANEMOI_N, ANEMOI_ROUNDS>>::GENERATOR;
+        let g_inv = <P as AnemoiJive<Fr, ANEMOI_N, ANEMOI_ROUNDS>>::GENERATOR_INV;
+        assert_eq!(g, Fr::from(5u64));
+        assert_eq!(g * g_inv, Fr::one());
+
+        let round_keys_x = <P as AnemoiJive<Fr, ANEMOI_N, ANEMOI_ROUNDS>>::ROUND_KEYS_X;
+        let round_keys_y = <P as AnemoiJive<Fr, ANEMOI_N, ANEMOI_ROUNDS>>::ROUND_KEYS_Y;
+        assert_eq!(round_keys_x[0][0], Fr::from(37u64));
+        for (i, (keys_x, keys_y)) in round_keys_x.iter().zip(round_keys_y.iter()).enumerate() {
+            let a = pi_0.pow([i as u64]);
+            for (j, (kx, ky)) in keys_x.iter().zip(keys_y).enumerate() {
+                let b = pi_1.pow([j as u64]);
+                let mixed = (a + b).pow([5]);
+                assert_eq!(*kx, g * a.square() + mixed);
+                assert_eq!(*ky, g * b.square() + mixed + g_inv);
+            }
+        }
+
+        // the precompile on x = [1, 2], y = [3, 4] with the keys above, pinned from uzkge 0.1.2
+        let word = |v: u64| Token::FixedBytes(Fr::from(v).into_bigint().to_bytes_be());
+        let data = ethabi::encode(&[
+            Token::Array(vec![word(1), word(2)]),
+            Token::Array(vec![word(3), word(4)]),
+        ]);
+        let mut ret = vec![0u8; 32 * 2 * ANEMOI_N];
+        eval_permutation(&data, &mut ret).unwrap();
+
+        let out: Vec<Fr> = ret.chunks(32).map(Fr::from_be_bytes_mod_order).collect();
+        let expected: [Fr; 2 * ANEMOI_N] = [
+            MontFp!("6185710248383500195340966629668597282662732493026422763743198761568169459607"),
+            MontFp!("5756288675789039888565800016001202296962739318562664741938136385042641802276"),
+            MontFp!(
+                "18820484380861548061116633527154947039807294201205846660001644833934953159642"
+            ),
+            MontFp!("1879429413404307990611511323313797589967153143104593612892787112297664425140"),
+        ];
+        assert_eq!(out, expected);
+
+        // Jive compression of the same state, the input plus the permuted state
+        let jive: Fr = (1..=4u64).map(Fr::from).chain(out).sum();
+        assert_eq!(
+            jive,
+            MontFp!(
+                "10753669846599120913388505750881269120851554755483493434877562906267620351058"
+            )
+        );
+    }
+
+    #[test]
+    fn anemoi_xof_works() {
+        let mut prng = ChaChaRng::from_seed([0u8; 32]);
+        let inputs = [Fr::rand(&mut prng), Fr::rand(&mut prng)];
+        let words = Token::Array(
+            inputs
+                .iter()
+                .map(|f| Token::FixedBytes(f.into_bigint().to_bytes_be()))
+                .collect(),
+        );
+
+        let squeeze = |count: usize| {
+            let data = ethabi::encode(&[words.clone(), Token::Uint(count.into())]);
+            let mut ret = vec![0u8; bytes32_array_len(count)];
+            eval_xof(&data, &mut ret).map(|_| ret)
+        };
+
+        let expected = AnemoiJive254::eval_stream_cipher(&inputs, 5);
+        let ret = squeeze(5).unwrap();
+        let rs = ethabi::decode(
+            &[ParamType::Array(Box::new(ParamType::FixedBytes(32)))],
+            &ret,
+        )
+        .unwrap();
+        let outputs = into_field_array(rs.first().cloned()).unwrap();
+        assert_eq!(outputs, expected);
+
+        // fewer outputs are a prefix of the same squeeze
+        let short = squeeze(2).unwrap();
+        assert_eq!(short[64..], ret[64..128]);
+
+        assert!(matches!(squeeze(0), Err(Error::UnsupportedSize)));
+        assert!(matches!(
+            squeeze(MAX_XOF_OUTPUTS + 1),
+            Err(Error::UnsupportedSize)
+        ));
+
+        let data = ethabi::encode(&[words.clone(), Token::Uint(2.into())]);
+        let mut ret = vec![0u8; bytes32_array_len(3)];
+        assert!(matches!(
+            eval_xof(&data, &mut ret),
+            Err(Error::LengthMismatch)
+        ));
+    }
+
+    #[test]
+    fn anemoi_bytes_works() {
+        let msg = b"zytron: player 0x01 moves e2e4 in game #42".to_vec();
+        assert_eq!(pack_bytes(&msg).len(), 3);
+
+        let mut ret = vec![0u8; 32];
+        eval_hash_bytes(&ethabi::encode(&[Token::Bytes(msg.clone())]), &mut ret).unwrap();
+        assert_eq!(ret, hash_bytes(&msg).into_bigint().to_bytes_be());
+
+        // trailing zero bytes and the empty message do not collide
+        let mut padded = msg.clone();
+        padded.push(0);
+        assert_ne!(hash_bytes(&msg), hash_bytes(&padded));
+        assert_ne!(hash_bytes(&[]), hash_bytes(&[0]));
+        assert_eq!(pack_bytes(&[]), vec![Fr::from(0u64)]);
+    }
+
+    #[test]
+    fn anemoi_batch_works() {
+        let mut prng = ChaChaRng::from_seed([0u8; 32]);
+        let messages: Vec<Vec<Fr>> = (0..5)
+            .map(|i| (0..i).map(|_| Fr::rand(&mut prng)).collect())
+            .collect();
+
+        let data = ethabi::encode(&[Token::Array(
+            messages
+                .iter()
+                .map(|m| {
+                    Token::Array(
+                        m.iter()
+                            .map(|f| Token::FixedBytes(f.into_bigint().to_bytes_be()))
+                            .collect(),
+                    )
+                })
+                .collect(),
+        )]);
+
+        let mut ret = vec![0u8; bytes32_array_len(messages.len())];
+        eval_batch_hash(&data, &mut ret).unwrap();
+
+        let rs = ethabi::decode(
+            &[ParamType::Array(Box::new(ParamType::FixedBytes(32)))],
+            &ret,
+        )
+        .unwrap();
+        let digests = into_field_array(rs.first().cloned()).unwrap();
+        for (m, d) in messages.iter().zip(digests.iter()) {
+            assert_eq!(AnemoiJive254::eval_variable_length_hash(m), *d);
+        }
+
+        let mut ret = vec![0u8; bytes32_array_len(messages.len() - 1)];
+        assert!(matches!(
+            eval_batch_hash(&data, &mut ret),
+            Err(Error::LengthMismatch)
+        ));
+    }
 }
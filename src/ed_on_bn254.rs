@@ -1,18 +1,43 @@
-use ark_ec::{AffineRepr, CurveGroup};
-use ark_ed_on_bn254::{EdwardsAffine, Fq, Fr};
-use ark_ff::{BigInteger, PrimeField};
+use alloc::{boxed::Box, vec, vec::Vec};
+use ark_ec::{AffineRepr, CurveGroup, VariableBaseMSM};
+use ark_ed_on_bn254::{EdwardsAffine, EdwardsProjective, Fq, Fr};
+use ark_ff::{AdditiveGroup, BigInteger, PrimeField, Zero};
 use ark_std::panic;
 use core::slice;
 use ethabi::ParamType;
+use lazy_static::lazy_static;
+use primitive_types::U256;
+use sha3::{Digest, Keccak256};
+use uzkge::anemoi::{AnemoiJive, AnemoiJive254};
 
 use crate::{utils, Error, Result};
 
 pub const POINY_ADD_GAS: u64 = 100;
+pub const POINT_NEG_GAS: u64 = 20;
+pub const POINT_SUB_GAS: u64 = 100;
+pub const POINT_DOUBLE_GAS: u64 = 100;
 pub const SCALAR_MUL_GAS: u64 = 200;
+pub const FIXED_BASE_MUL_GAS: u64 = 60;
+pub const SIGNATURE_CHALLENGE_GAS: u64 = 100;
+/// A single verification is the challenge hash and two scalar multiplications.
+pub const SIGNATURE_VERIFY_GAS: u64 = SIGNATURE_CHALLENGE_GAS + 2 * SCALAR_MUL_GAS;
+/// One base of the batch MSM. A full batch measures at about a quarter of a scalar
+/// multiplication per base, the rest covers the fixed cost of small batches.
+pub const BATCH_MSM_TERM_GAS: u64 = 75;
+/// The shared `sum(z_i * s_i) * G` term, paid once per batch.
+pub const BATCH_SIGNATURE_VERIFY_BASE_GAS: u64 = SCALAR_MUL_GAS;
+/// The challenge and the `R_i`, `PK_i` terms of each signature, half of a single verification.
+pub const BATCH_SIGNATURE_VERIFY_PER_GAS: u64 = SIGNATURE_CHALLENGE_GAS + 2 * BATCH_MSM_TERM_GAS;
+/// Largest batch, one signature from every player at a table.
+pub const MAX_BATCH_SIGNATURES: usize = 10;
 
 // support
 // 1. point add
-// 2. scalar mul
+// 2. point neg, sub and double
+// 3. scalar mul
+// 4. fixed-base scalar mul by the generator
+// 5. signature verify
+// 6. batch signature verify
 
 #[no_mangle]
 #[allow(clippy::not_unsafe_ptr_arg_deref)]
@@ -72,6 +97,143 @@ fn point_add(data: &[u8], ret: &mut [u8]) -> Result<()> {
     Ok(())
 }
 
+#[no_mangle]
+#[allow(clippy::not_unsafe_ptr_arg_deref)]
+pub extern "C" fn __precompile_ed_on_bn254_point_neg(
+    data_ptr: *const u8,
+    data_len: usize,
+    ret_val: *mut u8,
+) -> u8 {
+    let result = panic::catch_unwind(|| {
+        let data = unsafe { slice::from_raw_parts(data_ptr, data_len) };
+        let ret = unsafe { slice::from_raw_parts_mut(ret_val, 64) };
+
+        match point_neg(data, ret) {
+            Ok(()) => 0,
+            Err(e) => e.code(),
+        }
+    });
+    if let Ok(code) = result {
+        code
+    } else {
+        Error::Unknown.code()
+    }
+}
+
+#[no_mangle]
+#[allow(clippy::not_unsafe_ptr_arg_deref)]
+pub extern "C" fn __precompile_ed_on_bn254_point_neg_gas(_: *const u8, _: usize) -> u64 {
+    POINT_NEG_GAS
+}
+
+#[no_mangle]
+#[allow(clippy::not_unsafe_ptr_arg_deref)]
+pub extern "C" fn __precompile_ed_on_bn254_point_sub(
+    data_ptr: *const u8,
+    data_len: usize,
+    ret_val: *mut u8,
+) -> u8 {
+    let result = panic::catch_unwind(|| {
+        let data = unsafe { slice::from_raw_parts(data_ptr, data_len) };
+        let ret = unsafe { slice::from_raw_parts_mut(ret_val, 64) };
+
+        match point_sub(data, ret) {
+            Ok(()) => 0,
+            Err(e) => e.code(),
+        }
+    });
+    if let Ok(code) = result {
+        code
+    } else {
+        Error::Unknown.code()
+    }
+}
+
+#[no_mangle]
+#[allow(clippy::not_unsafe_ptr_arg_deref)]
+pub extern "C" fn __precompile_ed_on_bn254_point_sub_gas(_: *const u8, _: usize) -> u64 {
+    POINT_SUB_GAS
+}
+
+#[no_mangle]
+#[allow(clippy::not_unsafe_ptr_arg_deref)]
+pub extern "C" fn __precompile_ed_on_bn254_point_double(
+    data_ptr: *const u8,
+    data_len: usize,
+    ret_val: *mut u8,
+) -> u8 {
+    let result = panic::catch_unwind(|| {
+        let data = unsafe { slice::from_raw_parts(data_ptr, data_len) };
+        let ret = unsafe { slice::from_raw_parts_mut(ret_val, 64) };
+
+        match point_double(data, ret) {
+            Ok(()) => 0,
+            Err(e) => e.code(),
+        }
+    });
+    if let Ok(code) = result {
+        code
+    } else {
+        Error::Unknown.code()
+    }
+}
+
+#[no_mangle]
+#[allow(clippy::not_unsafe_ptr_arg_deref)]
+pub extern "C" fn __precompile_ed_on_bn254_point_double_gas(_: *const u8, _: usize) -> u64 {
+    POINT_DOUBLE_GAS
+}
+
+/// Decode `n` points laid out as consecutive `(x, y)` uint256 pairs, as `point_add` takes them.
+///
+/// Like `point_add`, coordinates are reduced modulo the base field and the point must be
+/// on the curve and in the prime subgroup. An invalid point makes `point_add` panic and
+/// report `Unknown`, here it is a `Deserialize` error.
+fn decode_points(data: &[u8], n: usize) -> Result<Vec<EdwardsAffine>> {
+    let r =
+        ethabi::decode(&vec![ParamType::Uint(256); 2 * n], data).map_err(|_| Error::Deserialize)?;
+
+    let mut points = Vec::with_capacity(n);
+    let mut tmp_bytes = [0u8; 32];
+    for i in 0..n {
+        let x = utils::into_uint256(r.get(2 * i).cloned()).ok_or(Error::Deserialize)?;
+        x.to_big_endian(&mut tmp_bytes);
+        let x = Fq::from_be_bytes_mod_order(&tmp_bytes);
+        let y = utils::into_uint256(r.get(2 * i + 1).cloned()).ok_or(Error::Deserialize)?;
+        y.to_big_endian(&mut tmp_bytes);
+        let y = Fq::from_be_bytes_mod_order(&tmp_bytes);
+
+        let p = EdwardsAffine::new_unchecked(x, y);
+        if !p.is_on_curve() || !p.is_in_correct_subgroup_assuming_on_curve() {
+            return Err(Error::Deserialize);
+        }
+        points.push(p);
+    }
+
+    Ok(points)
+}
+
+/// `-P`, input `(x, y)`.
+fn point_neg(data: &[u8], ret: &mut [u8]) -> Result<()> {
+    let p = decode_points(data, 1)?;
+
+    write_point(&-p[0], ret)
+}
+
+/// `P - Q`, input `(p_x, p_y, q_x, q_y)`.
+fn point_sub(data: &[u8], ret: &mut [u8]) -> Result<()> {
+    let p = decode_points(data, 2)?;
+
+    write_point(&(p[0] - p[1]).into_affine(), ret)
+}
+
+/// `2P`, input `(x, y)`.
+fn point_double(data: &[u8], ret: &mut [u8]) -> Result<()> {
+    let p = decode_points(data, 1)?;
+
+    write_point(&p[0].into_group().double().into_affine(), ret)
+}
+
 #[no_mangle]
 #[allow(clippy::not_unsafe_ptr_arg_deref)]
 pub extern "C" fn __precompile_ed_on_bn254_scalar_mul(
@@ -125,13 +287,385 @@ fn scalar_mul(data: &[u8], ret: &mut [u8]) -> Result<()> {
     Ok(())
 }
 
+/// Bits of the scalar consumed per window of the fixed-base table.
+const FIXED_BASE_WINDOW: usize = 4;
+
+lazy_static! {
+    /// `GENERATOR_TABLE[i][j] = j * 2^(4i) * G`, one row per window of a scalar.
+    static ref GENERATOR_TABLE: Vec<Vec<EdwardsAffine>> = {
+        let windows = (Fr::MODULUS_BIT_SIZE as usize).div_ceil(FIXED_BASE_WINDOW);
+        let mut base = EdwardsAffine::generator().into_group();
+        let mut table = Vec::with_capacity(windows);
+        for _ in 0..windows {
+            let mut row = Vec::with_capacity(1 << FIXED_BASE_WINDOW);
+            let mut acc = EdwardsProjective::zero();
+            for _ in 0..1 << FIXED_BASE_WINDOW {
+                row.push(acc);
+                acc += base;
+            }
+            table.push(EdwardsProjective::normalize_batch(&row));
+            base = acc;
+        }
+        table
+    };
+}
+
+/// Multiply the generator by `s` with the precomputed window table, one addition per window.
+pub fn generator_mul(s: &Fr) -> EdwardsAffine {
+    let bits = s.into_bigint().to_bits_le();
+
+    let mut acc = EdwardsProjective::zero();
+    for (row, window) in GENERATOR_TABLE.iter().zip(bits.chunks(FIXED_BASE_WINDOW)) {
+        let digit = window
+            .iter()
+            .rev()
+            .fold(0usize, |d, b| (d << 1) | *b as usize);
+        acc += row[digit];
+    }
+
+    acc.into_affine()
+}
+
+#[no_mangle]
+#[allow(clippy::not_unsafe_ptr_arg_deref)]
+pub extern "C" fn __precompile_ed_on_bn254_generator_mul(
+    data_ptr: *const u8,
+    data_len: usize,
+    ret_val: *mut u8,
+) -> u8 {
+    let result = panic::catch_unwind(|| {
+        let data = unsafe { slice::from_raw_parts(data_ptr, data_len) };
+        let ret = unsafe { slice::from_raw_parts_mut(ret_val, 64) };
+
+        match fixed_base_mul(data, ret) {
+            Ok(()) => 0,
+            Err(e) => e.code(),
+        }
+    });
+    if let Ok(code) = result {
+        code
+    } else {
+        Error::Unknown.code()
+    }
+}
+
+#[no_mangle]
+#[allow(clippy::not_unsafe_ptr_arg_deref)]
+pub extern "C" fn __precompile_ed_on_bn254_generator_mul_gas(_: *const u8, _: usize) -> u64 {
+    FIXED_BASE_MUL_GAS
+}
+
+fn fixed_base_mul(data: &[u8], ret: &mut [u8]) -> Result<()> {
+    let r = ethabi::decode(&[ParamType::Uint(256)], data).map_err(|_| Error::Deserialize)?;
+    let h = utils::into_uint256(r.first().cloned()).ok_or(Error::Deserialize)?;
+    let mut tmp_bytes = [0u8; 32];
+    h.to_big_endian(&mut tmp_bytes);
+    let s = Fr::from_be_bytes_mod_order(&tmp_bytes);
+
+    write_point(&generator_mul(&s), ret)
+}
+
+/// Hash used to derive the signature challenge `c = H(R, PK, m)`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ChallengeHash {
+    Anemoi,
+    Keccak256,
+}
+
+impl ChallengeHash {
+    pub fn from_u8(v: u8) -> Result<Self> {
+        match v {
+            0 => Ok(Self::Anemoi),
+            1 => Ok(Self::Keccak256),
+            _ => Err(Error::Deserialize),
+        }
+    }
+}
+
+#[no_mangle]
+#[allow(clippy::not_unsafe_ptr_arg_deref)]
+pub extern "C" fn __precompile_ed_on_bn254_verify_signature(
+    data_ptr: *const u8,
+    data_len: usize,
+) -> u8 {
+    let result = panic::catch_unwind(|| {
+        let data = unsafe { slice::from_raw_parts(data_ptr, data_len) };
+
+        match verify_signature(data) {
+            Ok(()) => 0,
+            Err(e) => e.code(),
+        }
+    });
+    if let Ok(code) = result {
+        code
+    } else {
+        Error::Unknown.code()
+    }
+}
+
+#[no_mangle]
+#[allow(clippy::not_unsafe_ptr_arg_deref)]
+pub extern "C" fn __precompile_ed_on_bn254_verify_signature_gas(_: *const u8, _: usize) -> u64 {
+    SIGNATURE_VERIFY_GAS
+}
+
+/// Decode a canonical base field element, rejecting values not below the modulus.
+pub(crate) fn into_fq(v: U256) -> Result<Fq> {
+    utils::into_field(v)
+}
+
+/// Decode a canonical scalar, rejecting values not below the group order.
+pub(crate) fn into_fr(v: U256) -> Result<Fr> {
+    utils::into_field(v)
+}
+
+/// Decode a point, checking that it is on the curve and in the prime subgroup.
+pub(crate) fn into_point(x: U256, y: U256) -> Result<EdwardsAffine> {
+    let p = EdwardsAffine::new_unchecked(into_fq(x)?, into_fq(y)?);
+    if !p.is_on_curve() || !p.is_in_correct_subgroup_assuming_on_curve() {
+        return Err(Error::Deserialize);
+    }
+    Ok(p)
+}
+
+/// Encode a point as `(x, y)`, the identity as `(0, 1)` like `into_point` accepts it.
+pub(crate) fn write_point(p: &EdwardsAffine, ret: &mut [u8]) -> Result<()> {
+    ret[0..32].copy_from_slice(&p.x.into_bigint().to_bytes_be());
+    ret[32..64].copy_from_slice(&p.y.into_bigint().to_bytes_be());
+    Ok(())
+}
+
+/// Compute the challenge `c = H(R.x, R.y, PK.x, PK.y, m)` reduced into the scalar field.
+///
+/// With Anemoi every input is a base field element and the digest is hashed with
+/// `AnemoiJive254`; with keccak256 the 32-byte big-endian encodings are concatenated.
+pub fn signature_challenge(
+    hash: ChallengeHash,
+    pk: &EdwardsAffine,
+    message: &[u8; 32],
+    r: &EdwardsAffine,
+) -> Result<Fr> {
+    let (pk_x, pk_y) = pk.xy().ok_or(Error::Serialize)?;
+    let (r_x, r_y) = r.xy().ok_or(Error::Serialize)?;
+
+    let digest = match hash {
+        ChallengeHash::Anemoi => {
+            let m = Fq::from_be_bytes_mod_order(message);
+            let h = AnemoiJive254::eval_variable_length_hash(&[r_x, r_y, pk_x, pk_y, m]);
+            h.into_bigint().to_bytes_be()
+        }
+        ChallengeHash::Keccak256 => {
+            let mut hasher = Keccak256::new();
+            hasher.update(r_x.into_bigint().to_bytes_be());
+            hasher.update(r_y.into_bigint().to_bytes_be());
+            hasher.update(pk_x.into_bigint().to_bytes_be());
+            hasher.update(pk_y.into_bigint().to_bytes_be());
+            hasher.update(message);
+            hasher.finalize().to_vec()
+        }
+    };
+
+    Ok(Fr::from_be_bytes_mod_order(&digest))
+}
+
+/// Check the Schnorr equation `s * G == R + c * PK`.
+pub fn check_signature(
+    hash: ChallengeHash,
+    pk: &EdwardsAffine,
+    message: &[u8; 32],
+    r: &EdwardsAffine,
+    s: &Fr,
+) -> Result<()> {
+    let c = signature_challenge(hash, pk, message, r)?;
+
+    if EdwardsAffine::generator() * s == *pk * c + r {
+        Ok(())
+    } else {
+        Err(Error::VerifyFail)
+    }
+}
+
+fn verify_signature(data: &[u8]) -> Result<()> {
+    let n = ParamType::Uint(256);
+    let r = ethabi::decode(
+        &[
+            ParamType::Uint(8),
+            n.clone(),
+            n.clone(),
+            ParamType::FixedBytes(32),
+            n.clone(),
+            n.clone(),
+            n,
+        ],
+        data,
+    )
+    .map_err(|_| Error::Deserialize)?;
+    let hash = utils::into_uint256(r.first().cloned()).ok_or(Error::Deserialize)?;
+    let pk_x = utils::into_uint256(r.get(1).cloned()).ok_or(Error::Deserialize)?;
+    let pk_y = utils::into_uint256(r.get(2).cloned()).ok_or(Error::Deserialize)?;
+    let message = utils::into_bytes32(r.get(3).cloned()).ok_or(Error::Deserialize)?;
+    let r_x = utils::into_uint256(r.get(4).cloned()).ok_or(Error::Deserialize)?;
+    let r_y = utils::into_uint256(r.get(5).cloned()).ok_or(Error::Deserialize)?;
+    let s = utils::into_uint256(r.get(6).cloned()).ok_or(Error::Deserialize)?;
+
+    let hash = ChallengeHash::from_u8(u8::try_from(hash).map_err(|_| Error::Deserialize)?)?;
+    let pk = into_point(pk_x, pk_y)?;
+    let r = into_point(r_x, r_y)?;
+    let s = into_fr(s)?;
+
+    check_signature(hash, &pk, &message, &r, &s)
+}
+
+/// A public key, the signed message and its signature `(R, s)`.
+#[derive(Clone, Debug)]
+pub struct SignedMessage {
+    pub pk: EdwardsAffine,
+    pub message: [u8; 32],
+    pub r: EdwardsAffine,
+    pub s: Fr,
+}
+
+#[no_mangle]
+#[allow(clippy::not_unsafe_ptr_arg_deref)]
+pub extern "C" fn __precompile_ed_on_bn254_batch_verify_signature(
+    data_ptr: *const u8,
+    data_len: usize,
+    ret_val: *mut u8,
+) -> u8 {
+    let result = panic::catch_unwind(|| {
+        let data = unsafe { slice::from_raw_parts(data_ptr, data_len) };
+        let ret = unsafe { slice::from_raw_parts_mut(ret_val, 32) };
+
+        match batch_verify_signature(data, ret) {
+            Ok(()) => 0,
+            Err(e) => e.code(),
+        }
+    });
+    if let Ok(code) = result {
+        code
+    } else {
+        Error::Unknown.code()
+    }
+}
+
+#[no_mangle]
+#[allow(clippy::not_unsafe_ptr_arg_deref)]
+pub extern "C" fn __precompile_ed_on_bn254_batch_verify_signature_gas(
+    data_ptr: *const u8,
+    data_len: usize,
+) -> u64 {
+    let data = unsafe { slice::from_raw_parts(data_ptr, data_len) };
+
+    let num = utils::abi_array_len(data, 1).unwrap_or(0) as u64;
+
+    BATCH_SIGNATURE_VERIFY_BASE_GAS + num * BATCH_SIGNATURE_VERIFY_PER_GAS
+}
+
+/// Verify all signatures with one multi-scalar multiplication.
+///
+/// Each equation is weighted by a 128-bit randomizer derived from `seed`, so the
+/// result is deterministic for the same call data. Returns whether every signature
+/// verified; a failing batch is not re-checked one by one, so the work stays within
+/// the charged gas.
+pub fn batch_check_signatures(
+    hash: ChallengeHash,
+    sigs: &[SignedMessage],
+    seed: &[u8],
+) -> Result<bool> {
+    if sigs.len() > MAX_BATCH_SIGNATURES {
+        return Err(Error::Deserialize);
+    }
+    if sigs.is_empty() {
+        return Ok(true);
+    }
+
+    let mut bases = Vec::with_capacity(2 * sigs.len() + 1);
+    let mut scalars = Vec::with_capacity(2 * sigs.len() + 1);
+    let mut s_sum = Fr::zero();
+
+    bases.push(EdwardsAffine::generator());
+    scalars.push(Fr::zero());
+
+    let challenges = utils::par_try_map(sigs, |sig| {
+        signature_challenge(hash, &sig.pk, &sig.message, &sig.r)
+    })?;
+
+    for (i, (sig, c)) in sigs.iter().zip(challenges).enumerate() {
+        let mut hasher = Keccak256::new();
+        hasher.update(seed);
+        hasher.update((i as u64).to_be_bytes());
+        let z = Fr::from_be_bytes_mod_order(&hasher.finalize()[..16]);
+
+        s_sum += z * sig.s;
+
+        bases.push(sig.r);
+        scalars.push(-z);
+        bases.push(sig.pk);
+        scalars.push(-(z * c));
+    }
+    scalars[0] = s_sum;
+
+    let sum = EdwardsProjective::msm(&bases, &scalars).map_err(|_| Error::Unknown)?;
+
+    Ok(sum.is_zero())
+}
+
+fn batch_verify_signature(data: &[u8], ret: &mut [u8]) -> Result<()> {
+    let n = ParamType::Uint(256);
+    let r = ethabi::decode(
+        &[
+            ParamType::Uint(8),
+            ParamType::Array(Box::new(ParamType::Tuple(vec![
+                n.clone(),
+                n.clone(),
+                ParamType::FixedBytes(32),
+                n.clone(),
+                n.clone(),
+                n,
+            ]))),
+        ],
+        data,
+    )
+    .map_err(|_| Error::Deserialize)?;
+    let hash = utils::into_uint256(r.first().cloned()).ok_or(Error::Deserialize)?;
+    let tokens = r
+        .get(1)
+        .and_then(|v| v.clone().into_array())
+        .ok_or(Error::Deserialize)?;
+
+    let hash = ChallengeHash::from_u8(u8::try_from(hash).map_err(|_| Error::Deserialize)?)?;
+
+    let mut sigs = Vec::with_capacity(tokens.len());
+    for token in tokens {
+        let t = token.into_tuple().ok_or(Error::Deserialize)?;
+        let pk_x = utils::into_uint256(t.first().cloned()).ok_or(Error::Deserialize)?;
+        let pk_y = utils::into_uint256(t.get(1).cloned()).ok_or(Error::Deserialize)?;
+        let message = utils::into_bytes32(t.get(2).cloned()).ok_or(Error::Deserialize)?;
+        let r_x = utils::into_uint256(t.get(3).cloned()).ok_or(Error::Deserialize)?;
+        let r_y = utils::into_uint256(t.get(4).cloned()).ok_or(Error::Deserialize)?;
+        let s = utils::into_uint256(t.get(5).cloned()).ok_or(Error::Deserialize)?;
+
+        sigs.push(SignedMessage {
+            pk: into_point(pk_x, pk_y)?,
+            message,
+            r: into_point(r_x, r_y)?,
+            s: into_fr(s)?,
+        });
+    }
+
+    let ok = batch_check_signatures(hash, &sigs, data)?;
+    ret.fill(0);
+    ret[31] = ok as u8;
+
+    Ok(())
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
-    use ark_ff::{vec, UniformRand};
+    use ark_ff::{vec, One, UniformRand};
     use ark_std::rand::SeedableRng;
     use ethabi::Token;
-    use primitive_types::U256;
     use rand_chacha::ChaChaRng;
 
     #[test]
@@ -202,4 +736,269 @@ mod tests {
         assert_eq!(p3_x, p3_0);
         assert_eq!(p3_y, p3_1);
     }
+
+    #[test]
+    fn ed_on_bn254_point_neg_sub_double_works() {
+        let mut prng = ChaChaRng::from_seed([0u8; 32]);
+        let p = EdwardsAffine::rand(&mut prng);
+        let q = EdwardsAffine::rand(&mut prng);
+
+        let encode = |points: &[EdwardsAffine]| {
+            let mut tokens = Vec::new();
+            for pt in points {
+                let (x, y) = pt.xy().unwrap();
+                tokens.push(Token::Uint(to_u256(x)));
+                tokens.push(Token::Uint(to_u256(y)));
+            }
+            ethabi::encode(&tokens)
+        };
+        let expect = |pt: EdwardsAffine| {
+            let mut bytes = vec![0u8; 64];
+            write_point(&pt, &mut bytes).unwrap();
+            bytes
+        };
+
+        let mut ret = vec![0u8; 64];
+        point_neg(&encode(&[p]), &mut ret).unwrap();
+        assert_eq!(ret, expect(-p));
+
+        point_sub(&encode(&[p, q]), &mut ret).unwrap();
+        assert_eq!(ret, expect((p + (-q)).into_affine()));
+
+        point_sub(&encode(&[p, p]), &mut ret).unwrap();
+        assert_eq!(ret, expect(EdwardsProjective::zero().into_affine()));
+
+        point_double(&encode(&[p]), &mut ret).unwrap();
+        assert_eq!(ret, expect((p + p).into_affine()));
+    }
+
+    #[test]
+    fn ed_on_bn254_point_ops_validate_like_add() {
+        let mut prng = ChaChaRng::from_seed([0u8; 32]);
+        let p = EdwardsAffine::rand(&mut prng);
+        let (x, y) = p.xy().unwrap();
+        let valid = [Token::Uint(to_u256(x)), Token::Uint(to_u256(y))];
+        let off_curve = [Token::Uint(U256::one()), Token::Uint(U256::one())];
+
+        let one = ethabi::encode(&off_curve);
+        let first = ethabi::encode(&[off_curve.clone(), valid.clone()].concat());
+        let second = ethabi::encode(&[valid.clone(), off_curve].concat());
+
+        type Op = fn(&[u8], &mut [u8]) -> Result<()>;
+        let ops: [(Op, Vec<&Vec<u8>>); 3] = [
+            (point_sub, vec![&first, &second]),
+            (point_neg, vec![&one]),
+            (point_double, vec![&one]),
+        ];
+
+        let mut ret = vec![0u8; 64];
+        for (op, inputs) in ops {
+            for data in inputs {
+                assert!(matches!(op(data, &mut ret), Err(Error::Deserialize)));
+            }
+        }
+        for data in [&first, &second] {
+            assert_eq!(
+                __precompile_ed_on_bn254_point_add(data.as_ptr(), data.len(), ret.as_mut_ptr()),
+                Error::Unknown.code()
+            );
+        }
+
+        // coordinates are reduced modulo the base field, as point_add does
+        let modulus = U256::from_big_endian(&Fq::MODULUS.to_bytes_be());
+        let unreduced = [Token::Uint(to_u256(x) + modulus), Token::Uint(to_u256(y))];
+        let mut expected = vec![0u8; 64];
+        point_neg(&ethabi::encode(&valid), &mut expected).unwrap();
+        point_neg(&ethabi::encode(&unreduced), &mut ret).unwrap();
+        assert_eq!(ret, expected);
+
+        point_add(
+            &ethabi::encode(&[valid.clone(), valid.clone()].concat()),
+            &mut expected,
+        )
+        .unwrap();
+        point_add(
+            &ethabi::encode(&[unreduced.clone(), valid.clone()].concat()),
+            &mut ret,
+        )
+        .unwrap();
+        assert_eq!(ret, expected);
+        point_double(&ethabi::encode(&unreduced), &mut ret).unwrap();
+        assert_eq!(ret, expected);
+    }
+
+    #[test]
+    fn ed_on_bn254_generator_mul_works() {
+        let mut prng = ChaChaRng::from_seed([0u8; 32]);
+        let g = EdwardsAffine::generator();
+
+        for s in [Fr::zero(), Fr::one(), -Fr::one(), Fr::rand(&mut prng)] {
+            let expected = (g * s).into_affine();
+            assert_eq!(generator_mul(&s), expected);
+
+            let scalar = U256::from_big_endian(&s.into_bigint().to_bytes_be());
+            let data = ethabi::encode(&[Token::Uint(scalar)]);
+            let mut ret = vec![0u8; 64];
+            fixed_base_mul(&data, &mut ret).unwrap();
+
+            let mut expected_bytes = vec![0u8; 64];
+            write_point(&expected, &mut expected_bytes).unwrap();
+            assert_eq!(ret, expected_bytes);
+        }
+    }
+
+    fn to_u256(f: Fq) -> U256 {
+        U256::from_big_endian(&f.into_bigint().to_bytes_be())
+    }
+
+    fn hash_type(hash: ChallengeHash) -> Token {
+        match hash {
+            ChallengeHash::Anemoi => Token::Uint(U256::zero()),
+            ChallengeHash::Keccak256 => Token::Uint(U256::one()),
+        }
+    }
+
+    fn sign(prng: &mut ChaChaRng, hash: ChallengeHash, message: [u8; 32]) -> SignedMessage {
+        let sk = Fr::rand(prng);
+        let pk = (EdwardsAffine::generator() * sk).into_affine();
+
+        let k = Fr::rand(prng);
+        let r = (EdwardsAffine::generator() * k).into_affine();
+        let c = signature_challenge(hash, &pk, &message, &r).unwrap();
+        let s = k + c * sk;
+
+        SignedMessage { pk, message, r, s }
+    }
+
+    fn signature_tokens(sig: &SignedMessage) -> Vec<Token> {
+        let (pk_x, pk_y) = sig.pk.xy().unwrap();
+        let (r_x, r_y) = sig.r.xy().unwrap();
+
+        vec![
+            Token::Uint(to_u256(pk_x)),
+            Token::Uint(to_u256(pk_y)),
+            Token::FixedBytes(sig.message.to_vec()),
+            Token::Uint(to_u256(r_x)),
+            Token::Uint(to_u256(r_y)),
+            Token::Uint(U256::from_big_endian(&sig.s.into_bigint().to_bytes_be())),
+        ]
+    }
+
+    #[test]
+    fn ed_on_bn254_verify_signature_works() {
+        let mut prng = ChaChaRng::from_seed([0u8; 32]);
+
+        for hash in [ChallengeHash::Anemoi, ChallengeHash::Keccak256] {
+            let sig = sign(&mut prng, hash, [7u8; 32]);
+            let mut tokens = vec![hash_type(hash)];
+            tokens.extend(signature_tokens(&sig));
+            let data = ethabi::encode(&tokens);
+
+            verify_signature(&data).unwrap();
+
+            // a different message must not verify against the same signature
+            let mut tampered = data.clone();
+            tampered[3 * 32 + 31] ^= 1;
+            assert!(matches!(
+                verify_signature(&tampered),
+                Err(Error::VerifyFail)
+            ));
+        }
+    }
+
+    #[test]
+    fn ed_on_bn254_batch_verify_signature_works() {
+        let mut prng = ChaChaRng::from_seed([0u8; 32]);
+
+        for hash in [ChallengeHash::Anemoi, ChallengeHash::Keccak256] {
+            let mut sigs = (0..10u8)
+                .map(|i| sign(&mut prng, hash, [i; 32]))
+                .collect::<Vec<_>>();
+
+            let encode = |sigs: &[SignedMessage]| {
+                ethabi::encode(&[
+                    hash_type(hash),
+                    Token::Array(
+                        sigs.iter()
+                            .map(|sig| Token::Tuple(signature_tokens(sig)))
+                            .collect(),
+                    ),
+                ])
+            };
+            let mut ret = vec![0u8; 32];
+
+            batch_verify_signature(&encode(&sigs), &mut ret).unwrap();
+            assert_eq!(U256::from_big_endian(&ret), U256::one());
+
+            // a full table costs less than verifying each signature on its own
+            let data = encode(&sigs);
+            let gas =
+                __precompile_ed_on_bn254_batch_verify_signature_gas(data.as_ptr(), data.len());
+            assert_eq!(gas, 200 + 10 * 250);
+            assert!(gas < 10 * SIGNATURE_VERIFY_GAS);
+
+            // more signatures than seats are rejected
+            let mut more = sigs.clone();
+            more.push(sign(&mut prng, hash, [10; 32]));
+            assert!(matches!(
+                batch_verify_signature(&encode(&more), &mut ret),
+                Err(Error::Deserialize)
+            ));
+
+            // break signature 2
+            sigs[2].message[0] ^= 1;
+            batch_verify_signature(&encode(&sigs), &mut ret).unwrap();
+            assert_eq!(U256::from_big_endian(&ret), U256::zero());
+
+            // break signature 7 as well
+            sigs[7].s += Fr::one();
+            batch_verify_signature(&encode(&sigs), &mut ret).unwrap();
+            assert_eq!(U256::from_big_endian(&ret), U256::zero());
+        }
+    }
+
+    #[test]
+    fn ed_on_bn254_batch_check_signatures_matches_sequential() {
+        // Run by `make test` both with and without `--features parallel`. Both builds
+        // must agree with verifying every signature on its own.
+        let mut prng = ChaChaRng::from_seed([1u8; 32]);
+
+        for hash in [ChallengeHash::Anemoi, ChallengeHash::Keccak256] {
+            let mut sigs = (0..MAX_BATCH_SIGNATURES as u8)
+                .map(|i| sign(&mut prng, hash, [i; 32]))
+                .collect::<Vec<_>>();
+            sigs[6].s += Fr::one();
+
+            let single = sigs
+                .iter()
+                .map(|sig| {
+                    let mut tokens = vec![hash_type(hash)];
+                    tokens.extend(signature_tokens(sig));
+                    verify_signature(&ethabi::encode(&tokens)).is_ok()
+                })
+                .collect::<Vec<_>>();
+            assert_eq!(single.iter().position(|ok| !ok), Some(6));
+            assert_eq!(single.iter().filter(|ok| !**ok).count(), 1);
+
+            let challenges = utils::par_try_map(&sigs, |sig| {
+                signature_challenge(hash, &sig.pk, &sig.message, &sig.r)
+            })
+            .unwrap();
+            for (sig, c) in sigs.iter().zip(challenges) {
+                assert_eq!(
+                    c,
+                    signature_challenge(hash, &sig.pk, &sig.message, &sig.r).unwrap()
+                );
+            }
+
+            // every prefix of the batch, with and without the invalid signature
+            for n in 1..=sigs.len() {
+                let expected = single[..n].iter().all(|ok| *ok);
+                assert_eq!(
+                    batch_check_signatures(hash, &sigs[..n], b"seed").unwrap(),
+                    expected
+                );
+            }
+        }
+    }
 }
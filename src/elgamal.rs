@@ -0,0 +1,563 @@
+use alloc::{boxed::Box, vec, vec::Vec};
+use ark_ec::{AffineRepr, CurveGroup};
+use ark_ed_on_bn254::EdwardsAffine;
+use ark_std::panic;
+use core::slice;
+use ethabi::{ParamType, Token};
+use primitive_types::U256;
+use zshuffle::{keygen::aggregate_keys, reveal, MaskedCard, RevealCard};
+
+use crate::{
+    ed_on_bn254::{
+        check_signature, into_fr, into_point, write_point, ChallengeHash, SIGNATURE_VERIFY_GAS,
+    },
+    plonk::bytes_2_masked_card,
+    utils, Error, Result,
+};
+
+pub const CIPHERTEXT_ADD_GAS: u64 = 200;
+pub const CIPHERTEXT_SCALAR_MUL_GAS: u64 = 400;
+pub const CIPHERTEXT_RERANDOMIZE_GAS: u64 = 400;
+pub const AGGREGATE_KEYS_BASE_GAS: u64 = 100;
+pub const UNMASK_BASE_GAS: u64 = 100;
+pub const UNMASK_WORD_GAS: u64 = 10;
+
+// ElGamal ciphertexts are masked cards `(e1, e2)`, passed as `bytes[4]`
+// `[e1.x, e1.y, e2.x, e2.y]` and returned as four 32-byte words in the same order.
+// support
+// 1. ciphertext add
+// 2. ciphertext scalar mul
+// 3. re-randomization
+// 4. joint public key aggregation
+// 5. unmasking
+
+#[no_mangle]
+#[allow(clippy::not_unsafe_ptr_arg_deref)]
+pub extern "C" fn __precompile_elgamal_add(
+    data_ptr: *const u8,
+    data_len: usize,
+    ret_val: *mut u8,
+) -> u8 {
+    let result = panic::catch_unwind(|| {
+        let data = unsafe { slice::from_raw_parts(data_ptr, data_len) };
+        let ret = unsafe { slice::from_raw_parts_mut(ret_val, 128) };
+
+        match ciphertext_add(data, ret) {
+            Ok(()) => 0,
+            Err(e) => e.code(),
+        }
+    });
+    if let Ok(code) = result {
+        code
+    } else {
+        Error::Unknown.code()
+    }
+}
+
+#[no_mangle]
+#[allow(clippy::not_unsafe_ptr_arg_deref)]
+pub extern "C" fn __precompile_elgamal_add_gas(_: *const u8, _: usize) -> u64 {
+    CIPHERTEXT_ADD_GAS
+}
+
+#[no_mangle]
+#[allow(clippy::not_unsafe_ptr_arg_deref)]
+pub extern "C" fn __precompile_elgamal_scalar_mul(
+    data_ptr: *const u8,
+    data_len: usize,
+    ret_val: *mut u8,
+) -> u8 {
+    let result = panic::catch_unwind(|| {
+        let data = unsafe { slice::from_raw_parts(data_ptr, data_len) };
+        let ret = unsafe { slice::from_raw_parts_mut(ret_val, 128) };
+
+        match ciphertext_scalar_mul(data, ret) {
+            Ok(()) => 0,
+            Err(e) => e.code(),
+        }
+    });
+    if let Ok(code) = result {
+        code
+    } else {
+        Error::Unknown.code()
+    }
+}
+
+#[no_mangle]
+#[allow(clippy::not_unsafe_ptr_arg_deref)]
+pub extern "C" fn __precompile_elgamal_scalar_mul_gas(_: *const u8, _: usize) -> u64 {
+    CIPHERTEXT_SCALAR_MUL_GAS
+}
+
+#[no_mangle]
+#[allow(clippy::not_unsafe_ptr_arg_deref)]
+pub extern "C" fn __precompile_elgamal_rerandomize(
+    data_ptr: *const u8,
+    data_len: usize,
+    ret_val: *mut u8,
+) -> u8 {
+    let result = panic::catch_unwind(|| {
+        let data = unsafe { slice::from_raw_parts(data_ptr, data_len) };
+        let ret = unsafe { slice::from_raw_parts_mut(ret_val, 128) };
+
+        match ciphertext_rerandomize(data, ret) {
+            Ok(()) => 0,
+            Err(e) => e.code(),
+        }
+    });
+    if let Ok(code) = result {
+        code
+    } else {
+        Error::Unknown.code()
+    }
+}
+
+#[no_mangle]
+#[allow(clippy::not_unsafe_ptr_arg_deref)]
+pub extern "C" fn __precompile_elgamal_rerandomize_gas(_: *const u8, _: usize) -> u64 {
+    CIPHERTEXT_RERANDOMIZE_GAS
+}
+
+pub(crate) fn write_masked_card(card: &MaskedCard, ret: &mut [u8]) -> Result<()> {
+    write_point(&card.e1.into_affine(), &mut ret[0..64])?;
+    write_point(&card.e2.into_affine(), &mut ret[64..128])
+}
+
+fn ciphertext_add(data: &[u8], ret: &mut [u8]) -> Result<()> {
+    let card = ParamType::Array(Box::new(ParamType::Bytes));
+    let r = ethabi::decode(&[card.clone(), card], data).map_err(|_| Error::Deserialize)?;
+    let c1 = utils::into_bytes_array(r.first().cloned()).ok_or(Error::Deserialize)?;
+    let c2 = utils::into_bytes_array(r.get(1).cloned()).ok_or(Error::Deserialize)?;
+    let c1 = bytes_2_masked_card(&c1)?;
+    let c2 = bytes_2_masked_card(&c2)?;
+
+    let res = MaskedCard {
+        e1: c1.e1 + c2.e1,
+        e2: c1.e2 + c2.e2,
+    };
+
+    write_masked_card(&res, ret)
+}
+
+fn ciphertext_scalar_mul(data: &[u8], ret: &mut [u8]) -> Result<()> {
+    let r = ethabi::decode(
+        &[
+            ParamType::Uint(256),
+            ParamType::Array(Box::new(ParamType::Bytes)),
+        ],
+        data,
+    )
+    .map_err(|_| Error::Deserialize)?;
+    let s = utils::into_uint256(r.first().cloned()).ok_or(Error::Deserialize)?;
+    let c = utils::into_bytes_array(r.get(1).cloned()).ok_or(Error::Deserialize)?;
+    let s = into_fr(s)?;
+    let c = bytes_2_masked_card(&c)?;
+
+    let res = MaskedCard {
+        e1: c.e1 * s,
+        e2: c.e2 * s,
+    };
+
+    write_masked_card(&res, ret)
+}
+
+/// Re-randomize `(e1, e2)` under public key `pk` with randomness `r`,
+/// giving `(e1 + r * G, e2 + r * pk)`.
+fn ciphertext_rerandomize(data: &[u8], ret: &mut [u8]) -> Result<()> {
+    let n = ParamType::Uint(256);
+    let r = ethabi::decode(
+        &[
+            n.clone(),
+            n.clone(),
+            n,
+            ParamType::Array(Box::new(ParamType::Bytes)),
+        ],
+        data,
+    )
+    .map_err(|_| Error::Deserialize)?;
+    let pk_x = utils::into_uint256(r.first().cloned()).ok_or(Error::Deserialize)?;
+    let pk_y = utils::into_uint256(r.get(1).cloned()).ok_or(Error::Deserialize)?;
+    let alpha = utils::into_uint256(r.get(2).cloned()).ok_or(Error::Deserialize)?;
+    let c = utils::into_bytes_array(r.get(3).cloned()).ok_or(Error::Deserialize)?;
+    let pk = into_point(pk_x, pk_y)?;
+    let alpha = into_fr(alpha)?;
+    let c = bytes_2_masked_card(&c)?;
+
+    let res = MaskedCard {
+        e1: c.e1 + EdwardsAffine::generator() * alpha,
+        e2: c.e2 + pk * alpha,
+    };
+
+    write_masked_card(&res, ret)
+}
+
+#[no_mangle]
+#[allow(clippy::not_unsafe_ptr_arg_deref)]
+pub extern "C" fn __precompile_aggregate_keys(
+    data_ptr: *const u8,
+    data_len: usize,
+    ret_val: *mut u8,
+) -> u8 {
+    let result = panic::catch_unwind(|| {
+        let data = unsafe { slice::from_raw_parts(data_ptr, data_len) };
+        let ret = unsafe { slice::from_raw_parts_mut(ret_val, 64) };
+
+        match aggregate_public_keys(data, ret) {
+            Ok(()) => 0,
+            Err(e) => e.code(),
+        }
+    });
+    if let Ok(code) = result {
+        code
+    } else {
+        Error::Unknown.code()
+    }
+}
+
+#[no_mangle]
+#[allow(clippy::not_unsafe_ptr_arg_deref)]
+pub extern "C" fn __precompile_aggregate_keys_gas(data_ptr: *const u8, data_len: usize) -> u64 {
+    let data = unsafe { slice::from_raw_parts(data_ptr, data_len) };
+
+    // head (context, offset, length) followed by 5 words per key
+    let num = data.len().saturating_sub(3 * 32) as u64 / (5 * 32);
+
+    AGGREGATE_KEYS_BASE_GAS + num * SIGNATURE_VERIFY_GAS
+}
+
+/// Validate every player key and its ownership proof, then aggregate them into the joint key.
+///
+/// The ownership proof of a key is a Schnorr signature by that key over `context`
+/// (for example the table id) with the Anemoi challenge hash, which rules out
+/// rogue keys chosen as a function of the other players' keys.
+fn aggregate_public_keys(data: &[u8], ret: &mut [u8]) -> Result<()> {
+    let n = ParamType::Uint(256);
+    let r = ethabi::decode(
+        &[
+            ParamType::FixedBytes(32),
+            ParamType::Array(Box::new(ParamType::Tuple(vec![
+                n.clone(),
+                n.clone(),
+                n.clone(),
+                n.clone(),
+                n,
+            ]))),
+        ],
+        data,
+    )
+    .map_err(|_| Error::Deserialize)?;
+    let context = utils::into_bytes32(r.first().cloned()).ok_or(Error::Deserialize)?;
+    let tokens = r
+        .get(1)
+        .and_then(|v| v.clone().into_array())
+        .ok_or(Error::Deserialize)?;
+
+    if tokens.is_empty() {
+        return Err(Error::Deserialize);
+    }
+
+    let mut pks = Vec::with_capacity(tokens.len());
+    for token in tokens {
+        let t = token.into_tuple().ok_or(Error::Deserialize)?;
+        let pk_x = utils::into_uint256(t.first().cloned()).ok_or(Error::Deserialize)?;
+        let pk_y = utils::into_uint256(t.get(1).cloned()).ok_or(Error::Deserialize)?;
+        let r_x = utils::into_uint256(t.get(2).cloned()).ok_or(Error::Deserialize)?;
+        let r_y = utils::into_uint256(t.get(3).cloned()).ok_or(Error::Deserialize)?;
+        let s = utils::into_uint256(t.get(4).cloned()).ok_or(Error::Deserialize)?;
+
+        let pk = into_point(pk_x, pk_y)?;
+        if pk.is_zero() {
+            return Err(Error::Deserialize);
+        }
+        let r = into_point(r_x, r_y)?;
+        let s = into_fr(s)?;
+
+        check_signature(ChallengeHash::Anemoi, &pk, &context, &r, &s)?;
+
+        pks.push(pk.into_group());
+    }
+
+    let joint_pk = aggregate_keys(&pks).map_err(|_| Error::Deserialize)?;
+
+    write_point(&joint_pk.into_affine(), ret)
+}
+
+#[no_mangle]
+#[allow(clippy::not_unsafe_ptr_arg_deref)]
+pub extern "C" fn __precompile_unmask(
+    data_ptr: *const u8,
+    data_len: usize,
+    ret_val: *mut u8,
+) -> u8 {
+    let result = panic::catch_unwind(|| {
+        let data = unsafe { slice::from_raw_parts(data_ptr, data_len) };
+        let ret = unsafe { slice::from_raw_parts_mut(ret_val, 96) };
+
+        match unmask(data, ret) {
+            Ok(()) => 0,
+            Err(e) => e.code(),
+        }
+    });
+    if let Ok(code) = result {
+        code
+    } else {
+        Error::Unknown.code()
+    }
+}
+
+#[no_mangle]
+#[allow(clippy::not_unsafe_ptr_arg_deref)]
+pub extern "C" fn __precompile_unmask_gas(data_ptr: *const u8, data_len: usize) -> u64 {
+    let data = unsafe { slice::from_raw_parts(data_ptr, data_len) };
+
+    UNMASK_BASE_GAS + data.len() as u64 / 32 * UNMASK_WORD_GAS
+}
+
+fn into_points(tk: Option<Token>) -> Result<Vec<EdwardsAffine>> {
+    let tokens = tk.and_then(|v| v.into_array()).ok_or(Error::Deserialize)?;
+
+    let mut res = Vec::with_capacity(tokens.len());
+    for token in tokens {
+        let xy = token.into_fixed_array().ok_or(Error::Deserialize)?;
+        let x = utils::into_uint256(xy.first().cloned()).ok_or(Error::Deserialize)?;
+        let y = utils::into_uint256(xy.get(1).cloned()).ok_or(Error::Deserialize)?;
+        res.push(into_point(x, y)?);
+    }
+
+    Ok(res)
+}
+
+/// Recover the card `e2 - sum(tokens)` from a masked card and all reveal tokens with
+/// `zshuffle::reveal::unmask`.
+///
+/// The output is the card point followed by its index in `known_cards`. The check is
+/// optional: when `known_cards` is empty the index is `type(uint256).max`, otherwise
+/// a card that is not in the list fails verification.
+fn unmask(data: &[u8], ret: &mut [u8]) -> Result<()> {
+    let point = ParamType::Array(Box::new(ParamType::FixedArray(
+        Box::new(ParamType::Uint(256)),
+        2,
+    )));
+    let r = ethabi::decode(
+        &[
+            ParamType::Array(Box::new(ParamType::Bytes)),
+            point.clone(),
+            point,
+        ],
+        data,
+    )
+    .map_err(|_| Error::Deserialize)?;
+    let card = utils::into_bytes_array(r.first().cloned()).ok_or(Error::Deserialize)?;
+    let card = bytes_2_masked_card(&card)?;
+    let tokens = into_points(r.get(1).cloned())?;
+    let known_cards = into_points(r.get(2).cloned())?;
+
+    if tokens.is_empty() {
+        return Err(Error::Deserialize);
+    }
+
+    let tokens: Vec<RevealCard> = tokens.iter().map(|t| t.into_group()).collect();
+    let plain = reveal::unmask(&card, &tokens)
+        .map_err(|_| Error::VerifyFail)?
+        .into_affine();
+
+    let index = if known_cards.is_empty() {
+        U256::MAX
+    } else {
+        known_cards
+            .iter()
+            .position(|v| *v == plain)
+            .map(U256::from)
+            .ok_or(Error::VerifyFail)?
+    };
+
+    write_point(&plain, &mut ret[0..64])?;
+    index.to_big_endian(&mut ret[64..96]);
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ed_on_bn254::signature_challenge;
+    use ark_ed_on_bn254::{EdwardsProjective, Fr};
+    use ark_ff::{BigInteger, PrimeField, UniformRand};
+    use ark_std::rand::SeedableRng;
+    use rand_chacha::ChaChaRng;
+    use zshuffle::{keygen::Keypair, mask::mask, Card};
+
+    fn card_token(card: &MaskedCard) -> Token {
+        let mut ret = vec![0u8; 128];
+        write_masked_card(card, &mut ret).unwrap();
+        Token::Array(ret.chunks(32).map(|v| Token::Bytes(v.to_vec())).collect())
+    }
+
+    fn into_masked_card(ret: &[u8]) -> MaskedCard {
+        let coords = ret.chunks(32).map(|v| v.to_vec()).collect::<Vec<_>>();
+        bytes_2_masked_card(&coords).unwrap()
+    }
+
+    fn to_u256<F: PrimeField>(f: F) -> U256 {
+        U256::from_big_endian(&f.into_bigint().to_bytes_be())
+    }
+
+    #[test]
+    fn elgamal_add_and_scalar_mul_works() {
+        let mut prng = ChaChaRng::from_seed([0u8; 32]);
+        let c1 = MaskedCard {
+            e1: EdwardsProjective::rand(&mut prng),
+            e2: EdwardsProjective::rand(&mut prng),
+        };
+        let c2 = MaskedCard {
+            e1: EdwardsProjective::rand(&mut prng),
+            e2: EdwardsProjective::rand(&mut prng),
+        };
+        let s = Fr::rand(&mut prng);
+        let mut ret = vec![0u8; 128];
+
+        let data = ethabi::encode(&[card_token(&c1), card_token(&c2)]);
+        ciphertext_add(&data, &mut ret).unwrap();
+        let res = into_masked_card(&ret);
+        assert_eq!(res.e1, c1.e1 + c2.e1);
+        assert_eq!(res.e2, c1.e2 + c2.e2);
+
+        let data = ethabi::encode(&[Token::Uint(to_u256(s)), card_token(&c1)]);
+        ciphertext_scalar_mul(&data, &mut ret).unwrap();
+        let res = into_masked_card(&ret);
+        assert_eq!(res.e1, c1.e1 * s);
+        assert_eq!(res.e2, c1.e2 * s);
+    }
+
+    #[test]
+    fn elgamal_rerandomize_matches_mask() {
+        let mut prng = ChaChaRng::from_seed([0u8; 32]);
+        let keypair = Keypair::generate(&mut prng);
+        let card = Card::rand(&mut prng);
+        let alpha = Fr::rand(&mut prng);
+        let beta = Fr::rand(&mut prng);
+
+        let (masked, _) = mask(&mut prng, &keypair.public, &card, &alpha).unwrap();
+        let (expected, _) = mask(&mut prng, &keypair.public, &card, &(alpha + beta)).unwrap();
+
+        let (pk_x, pk_y) = keypair.public.into_affine().xy().unwrap();
+        let data = ethabi::encode(&[
+            Token::Uint(to_u256(pk_x)),
+            Token::Uint(to_u256(pk_y)),
+            Token::Uint(to_u256(beta)),
+            card_token(&masked),
+        ]);
+        let mut ret = vec![0u8; 128];
+        ciphertext_rerandomize(&data, &mut ret).unwrap();
+
+        let res = into_masked_card(&ret);
+        assert_eq!(res.e1, expected.e1);
+        assert_eq!(res.e2, expected.e2);
+    }
+
+    #[test]
+    fn aggregate_keys_works() {
+        let mut prng = ChaChaRng::from_seed([0u8; 32]);
+        let context = [9u8; 32];
+        let keypairs = (0..3)
+            .map(|_| Keypair::generate(&mut prng))
+            .collect::<Vec<_>>();
+
+        let mut proofs = Vec::new();
+        for keypair in keypairs.iter() {
+            let pk = keypair.public.into_affine();
+            let k = Fr::rand(&mut prng);
+            let r = (EdwardsAffine::generator() * k).into_affine();
+            let c = signature_challenge(ChallengeHash::Anemoi, &pk, &context, &r).unwrap();
+            let s = k + c * keypair.secret;
+
+            let (pk_x, pk_y) = pk.xy().unwrap();
+            let (r_x, r_y) = r.xy().unwrap();
+            proofs.push(vec![
+                Token::Uint(to_u256(pk_x)),
+                Token::Uint(to_u256(pk_y)),
+                Token::Uint(to_u256(r_x)),
+                Token::Uint(to_u256(r_y)),
+                Token::Uint(to_u256(s)),
+            ]);
+        }
+
+        let encode = |proofs: &[Vec<Token>]| {
+            ethabi::encode(&[
+                Token::FixedBytes(context.to_vec()),
+                Token::Array(proofs.iter().cloned().map(Token::Tuple).collect()),
+            ])
+        };
+        let mut ret = vec![0u8; 64];
+
+        aggregate_public_keys(&encode(&proofs), &mut ret).unwrap();
+
+        let pks = keypairs.iter().map(|v| v.public).collect::<Vec<_>>();
+        let joint_pk = aggregate_keys(&pks).unwrap().into_affine();
+        let (x, y) = joint_pk.xy().unwrap();
+        assert_eq!(&ret[0..32], &x.into_bigint().to_bytes_be()[..]);
+        assert_eq!(&ret[32..64], &y.into_bigint().to_bytes_be()[..]);
+
+        // a proof made for another key must be rejected
+        proofs[1][4] = proofs[0][4].clone();
+        assert!(matches!(
+            aggregate_public_keys(&encode(&proofs), &mut ret),
+            Err(Error::VerifyFail)
+        ));
+    }
+
+    #[test]
+    fn unmask_works() {
+        let mut prng = ChaChaRng::from_seed([0u8; 32]);
+        let keypairs = (0..3)
+            .map(|_| Keypair::generate(&mut prng))
+            .collect::<Vec<_>>();
+        let pks = keypairs.iter().map(|v| v.public).collect::<Vec<_>>();
+        let joint_pk = aggregate_keys(&pks).unwrap();
+
+        let cards = (0..5).map(|_| Card::rand(&mut prng)).collect::<Vec<_>>();
+        let alpha = Fr::rand(&mut prng);
+        let (masked, _) = mask(&mut prng, &joint_pk, &cards[3], &alpha).unwrap();
+
+        let point_token = |p: &EdwardsProjective| {
+            let (x, y) = p.into_affine().xy().unwrap();
+            Token::FixedArray(vec![Token::Uint(to_u256(x)), Token::Uint(to_u256(y))])
+        };
+        let tokens = keypairs
+            .iter()
+            .map(|v| point_token(&(masked.e1 * v.secret)))
+            .collect::<Vec<_>>();
+        let known_cards = cards.iter().map(point_token).collect::<Vec<_>>();
+
+        let mut ret = vec![0u8; 96];
+        let data = ethabi::encode(&[
+            card_token(&masked),
+            Token::Array(tokens.clone()),
+            Token::Array(known_cards),
+        ]);
+        unmask(&data, &mut ret).unwrap();
+
+        let (x, y) = cards[3].into_affine().xy().unwrap();
+        assert_eq!(&ret[0..32], &x.into_bigint().to_bytes_be()[..]);
+        assert_eq!(&ret[32..64], &y.into_bigint().to_bytes_be()[..]);
+        assert_eq!(U256::from_big_endian(&ret[64..96]), U256::from(3));
+
+        // without known cards the card is returned unchecked, with index uint256 max
+        let data = ethabi::encode(&[
+            card_token(&masked),
+            Token::Array(tokens.clone()),
+            Token::Array(vec![]),
+        ]);
+        unmask(&data, &mut ret).unwrap();
+        assert_eq!(&ret[0..32], &x.into_bigint().to_bytes_be()[..]);
+        assert_eq!(U256::from_big_endian(&ret[64..96]), U256::MAX);
+
+        // with a missing reveal token the card is not one of the known cards
+        let data = ethabi::encode(&[
+            card_token(&masked),
+            Token::Array(tokens[..2].to_vec()),
+            Token::Array(cards.iter().map(point_token).collect()),
+        ]);
+        assert!(matches!(unmask(&data, &mut ret), Err(Error::VerifyFail)));
+    }
+}
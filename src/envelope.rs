@@ -0,0 +1,198 @@
+use alloc::vec::Vec;
+
+use crate::{Error, Result};
+
+// Self-describing wrapper for verifier params and proofs:
+//
+// | magic (4) | version (1) | curve (1) | circuit (1) | encoding (1) | payload |
+//
+// Bytes that do not start with the magic are legacy unversioned inputs and are
+// decoded with the format each verifier used before envelopes existed.
+
+pub const ENVELOPE_MAGIC: [u8; 4] = *b"ZYTN";
+pub const ENVELOPE_VERSION: u8 = 1;
+pub const ENVELOPE_HEADER_LEN: usize = 8;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Curve {
+    Bn254,
+    Bls12_381,
+}
+
+impl Curve {
+    pub fn id(&self) -> u8 {
+        match self {
+            Self::Bn254 => 0,
+            Self::Bls12_381 => 1,
+        }
+    }
+
+    pub fn from_id(id: u8) -> Result<Self> {
+        match id {
+            0 => Ok(Self::Bn254),
+            1 => Ok(Self::Bls12_381),
+            _ => Err(Error::UnsupportedVersion),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Circuit {
+    Plonk,
+    Shuffle,
+    Matchmaking,
+}
+
+impl Circuit {
+    pub fn id(&self) -> u8 {
+        match self {
+            Self::Plonk => 0,
+            Self::Shuffle => 1,
+            Self::Matchmaking => 2,
+        }
+    }
+
+    pub fn from_id(id: u8) -> Result<Self> {
+        match id {
+            0 => Ok(Self::Plonk),
+            1 => Ok(Self::Shuffle),
+            2 => Ok(Self::Matchmaking),
+            _ => Err(Error::UnsupportedVersion),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Encoding {
+    /// `bincode::serialize`
+    Bincode,
+    /// `PlonkProof::to_bytes_be`
+    BytesBe,
+    /// `params::to_canonical_bytes`
+    Canonical,
+}
+
+impl Encoding {
+    pub fn id(&self) -> u8 {
+        match self {
+            Self::Bincode => 0,
+            Self::BytesBe => 1,
+            Self::Canonical => 2,
+        }
+    }
+
+    pub fn from_id(id: u8) -> Result<Self> {
+        match id {
+            0 => Ok(Self::Bincode),
+            1 => Ok(Self::BytesBe),
+            2 => Ok(Self::Canonical),
+            _ => Err(Error::UnsupportedVersion),
+        }
+    }
+}
+
+/// Wrap `payload` in an envelope of the current version.
+pub fn seal(curve: Curve, circuit: Circuit, encoding: Encoding, payload: &[u8]) -> Vec<u8> {
+    let mut v = Vec::with_capacity(ENVELOPE_HEADER_LEN + payload.len());
+    v.extend_from_slice(&ENVELOPE_MAGIC);
+    v.push(ENVELOPE_VERSION);
+    v.push(curve.id());
+    v.push(circuit.id());
+    v.push(encoding.id());
+    v.extend_from_slice(payload);
+    v
+}
+
+/// Unwrap `bytes` for `circuit` over `curve`, returning the payload and its encoding.
+///
+/// Legacy inputs without the magic are returned as is with the `legacy` encoding.
+/// Unknown versions, curves, circuits or encodings are rejected with `UnsupportedVersion`,
+/// and an envelope made for another curve or circuit with `Deserialize`.
+pub fn open(
+    bytes: &[u8],
+    curve: Curve,
+    circuit: Circuit,
+    legacy: Encoding,
+) -> Result<(Encoding, &[u8])> {
+    if !bytes.starts_with(&ENVELOPE_MAGIC) {
+        return Ok((legacy, bytes));
+    }
+
+    let header = bytes.get(..ENVELOPE_HEADER_LEN).ok_or(Error::Deserialize)?;
+    if header[4] != ENVELOPE_VERSION {
+        return Err(Error::UnsupportedVersion);
+    }
+    if Curve::from_id(header[5])? != curve {
+        return Err(Error::Deserialize);
+    }
+    if Circuit::from_id(header[6])? != circuit {
+        return Err(Error::Deserialize);
+    }
+    let encoding = Encoding::from_id(header[7])?;
+
+    Ok((encoding, &bytes[ENVELOPE_HEADER_LEN..]))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn envelope_works() {
+        let payload = [1u8, 2, 3, 4];
+
+        let sealed = seal(Curve::Bn254, Circuit::Shuffle, Encoding::BytesBe, &payload);
+        let (encoding, opened) =
+            open(&sealed, Curve::Bn254, Circuit::Shuffle, Encoding::Bincode).unwrap();
+        assert_eq!(encoding, Encoding::BytesBe);
+        assert_eq!(opened, &payload[..]);
+
+        // legacy bytes are passed through with the legacy encoding
+        let (encoding, opened) =
+            open(&payload, Curve::Bn254, Circuit::Shuffle, Encoding::BytesBe).unwrap();
+        assert_eq!(encoding, Encoding::BytesBe);
+        assert_eq!(opened, &payload[..]);
+
+        // wrong circuit
+        assert!(matches!(
+            open(
+                &sealed,
+                Curve::Bn254,
+                Circuit::Matchmaking,
+                Encoding::Bincode
+            ),
+            Err(Error::Deserialize)
+        ));
+
+        // wrong curve
+        assert!(matches!(
+            open(
+                &sealed,
+                Curve::Bls12_381,
+                Circuit::Shuffle,
+                Encoding::BytesBe
+            ),
+            Err(Error::Deserialize)
+        ));
+
+        // unknown version, curve and encoding
+        let mut unknown = sealed.clone();
+        unknown[4] = ENVELOPE_VERSION + 1;
+        assert!(matches!(
+            open(&unknown, Curve::Bn254, Circuit::Shuffle, Encoding::BytesBe),
+            Err(Error::UnsupportedVersion)
+        ));
+        let mut unknown = sealed.clone();
+        unknown[5] = 0xff;
+        assert!(matches!(
+            open(&unknown, Curve::Bn254, Circuit::Shuffle, Encoding::BytesBe),
+            Err(Error::UnsupportedVersion)
+        ));
+        let mut unknown = sealed;
+        unknown[7] = 0xff;
+        assert!(matches!(
+            open(&unknown, Curve::Bn254, Circuit::Shuffle, Encoding::BytesBe),
+            Err(Error::UnsupportedVersion)
+        ));
+    }
+}
@@ -4,6 +4,12 @@ pub enum Error {
     Deserialize,
     VerifyFail,
     Unknown,
+    UnsupportedVersion,
+    LengthMismatch,
+    UnsupportedSize,
+    ParamsMismatch,
+    /// A card of a deck is malformed, with its index across the input then output deck.
+    InvalidCard(usize),
 }
 
 impl Error {
@@ -13,6 +19,11 @@ impl Error {
             Self::Deserialize => 2,
             Self::VerifyFail => 3,
             Self::Unknown => 4,
+            Self::UnsupportedVersion => 5,
+            Self::LengthMismatch => 6,
+            Self::UnsupportedSize => 7,
+            Self::ParamsMismatch => 8,
+            Self::InvalidCard(_) => 9,
         }
     }
 }
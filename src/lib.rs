@@ -7,8 +7,19 @@ pub mod ed_on_bn254;
 
 pub mod anemoi;
 
+pub mod merkle;
+
+pub mod poseidon;
+mod poseidon_constants;
+
+pub mod elgamal;
+
 pub mod plonk;
 
+pub mod envelope;
+
+pub mod params;
+
 pub mod error;
 pub use error::*;
 
@@ -0,0 +1,552 @@
+use alloc::boxed::Box;
+use alloc::vec::Vec;
+use ark_bn254::Fr;
+use ark_ff::{BigInteger, PrimeField, Zero};
+use ark_std::panic;
+use core::slice;
+use ethabi::{ParamType, Token};
+use lazy_static::lazy_static;
+use primitive_types::U256;
+use uzkge::anemoi::{AnemoiJive, AnemoiJive254};
+
+use crate::{
+    anemoi::{into_field_array, ANEMOI_EVAL},
+    utils, Error, Result,
+};
+
+/// Max depth of the incremental trees handled by `__precompile_merkle_append`.
+pub const MAX_MERKLE_DEPTH: usize = 32;
+/// Max depth of the sparse trees, a key has at most as many bits as `Fr`.
+pub const MAX_SMT_DEPTH: usize = 254;
+
+lazy_static! {
+    /// Roots of the empty subtrees, `EMPTY_ROOTS[i]` is the root of an empty subtree of height `i`.
+    /// Only the incremental trees use them, sparse tree proofs carry every sibling.
+    static ref EMPTY_ROOTS: Vec<Fr> = {
+        let mut roots = Vec::with_capacity(MAX_MERKLE_DEPTH + 1);
+        roots.push(Fr::zero());
+        for i in 0..MAX_MERKLE_DEPTH {
+            roots.push(compress(&roots[i], &roots[i]));
+        }
+        roots
+    };
+}
+
+/// Compress two children into their parent with the `AnemoiJive254` sponge.
+///
+/// This is `eval_variable_length_hash(&[left, right])`, which uzkge circuits compute
+/// with `TurboCS::anemoi_variable_length_hash`. The empty leaf is `0`.
+pub fn compress(left: &Fr, right: &Fr) -> Fr {
+    AnemoiJive254::eval_variable_length_hash(&[*left, *right])
+}
+
+/// Leaf of a sparse tree holding `value` under `key`.
+///
+/// The leaf is `eval_variable_length_hash(&[key, value, 0])`. That is one full
+/// block, while an inner node absorbs `[left, right]` padded to `[left, right, 1]`,
+/// so a leaf never collides with an inner node. An empty slot is the empty leaf
+/// `0`, a zero value is never stored. uzkge circuits compute it with
+/// `TurboCS::anemoi_variable_length_hash` over `[key, value, zero]`.
+pub fn smt_leaf(key: &Fr, value: &Fr) -> Fr {
+    AnemoiJive254::eval_variable_length_hash(&[*key, *value, Fr::zero()])
+}
+
+/// Root of an empty subtree of height `height`.
+pub fn empty_root(height: usize) -> Fr {
+    EMPTY_ROOTS[height]
+}
+
+#[no_mangle]
+#[allow(clippy::not_unsafe_ptr_arg_deref)]
+pub extern "C" fn __precompile_merkle_append(
+    data_ptr: *const u8,
+    data_len: usize,
+    ret_val: *mut u8,
+    ret_len: usize,
+) -> u8 {
+    let result = panic::catch_unwind(|| {
+        let data = unsafe { slice::from_raw_parts(data_ptr, data_len) };
+        let ret = unsafe { slice::from_raw_parts_mut(ret_val, ret_len) };
+        match eval_merkle_append(data, ret) {
+            Ok(()) => 0,
+            Err(e) => e.code(),
+        }
+    });
+    if let Ok(code) = result {
+        code
+    } else {
+        Error::Unknown.code()
+    }
+}
+
+#[no_mangle]
+#[allow(clippy::not_unsafe_ptr_arg_deref)]
+pub extern "C" fn __precompile_merkle_append_gas(data_ptr: *const u8, data_len: usize) -> u64 {
+    let data = unsafe { slice::from_raw_parts(data_ptr, data_len) };
+
+    // one compression per level
+    let depth = utils::abi_array_len(data, 0).unwrap_or(0).max(1) as u64;
+
+    depth * ANEMOI_EVAL
+}
+
+/// Byte length of the ABI encoded `(bytes32[] frontier, bytes32 root)` for a tree of `depth`.
+pub fn append_output_len(depth: usize) -> usize {
+    96 + depth * 32
+}
+
+/// Append `leaf` at `index` to an incremental tree.
+///
+/// `frontier[i]` is the last filled left node at level `i`, it is updated in
+/// place and the new root is returned. The leaf at `index` must be the next
+/// free one, which the caller tracks.
+pub fn merkle_append(frontier: &mut [Fr], index: u64, leaf: Fr) -> Result<Fr> {
+    let depth = frontier.len();
+    if depth == 0 || depth > MAX_MERKLE_DEPTH {
+        return Err(Error::UnsupportedSize);
+    }
+    if index >> depth != 0 {
+        return Err(Error::UnsupportedSize);
+    }
+
+    let mut current_index = index;
+    let mut current = leaf;
+    for (i, filled) in frontier.iter_mut().enumerate() {
+        current = if current_index.is_multiple_of(2) {
+            *filled = current;
+            compress(&current, &empty_root(i))
+        } else {
+            compress(filled, &current)
+        };
+        current_index /= 2;
+    }
+
+    Ok(current)
+}
+
+fn eval_merkle_append(data: &[u8], ret: &mut [u8]) -> Result<()> {
+    let rs = ethabi::decode(
+        &[
+            ParamType::Array(Box::new(ParamType::FixedBytes(32))),
+            ParamType::Uint(256),
+            ParamType::FixedBytes(32),
+        ],
+        data,
+    )
+    .map_err(|_| Error::Deserialize)?;
+    let mut frontier = into_field_array(rs.first().cloned())?;
+    let index = utils::into_uint256(rs.get(1).cloned()).ok_or(Error::Deserialize)?;
+    let leaf = utils::into_bytes32(rs.get(2).cloned()).ok_or(Error::Deserialize)?;
+
+    if index > U256::from(u64::MAX) {
+        return Err(Error::UnsupportedSize);
+    }
+    if ret.len() != append_output_len(frontier.len()) {
+        return Err(Error::LengthMismatch);
+    }
+
+    let root = merkle_append(
+        &mut frontier,
+        index.as_u64(),
+        Fr::from_be_bytes_mod_order(&leaf),
+    )?;
+
+    let frontier = frontier
+        .iter()
+        .map(|f| Token::FixedBytes(f.into_bigint().to_bytes_be()))
+        .collect();
+    let root = Token::FixedBytes(root.into_bigint().to_bytes_be());
+
+    ret.copy_from_slice(&ethabi::encode(&[Token::Array(frontier), root]));
+
+    Ok(())
+}
+
+#[no_mangle]
+#[allow(clippy::not_unsafe_ptr_arg_deref)]
+pub extern "C" fn __precompile_smt_verify(data_ptr: *const u8, data_len: usize) -> u8 {
+    let result = panic::catch_unwind(|| {
+        let data = unsafe { slice::from_raw_parts(data_ptr, data_len) };
+        match smt_verify(data) {
+            Ok(()) => 0,
+            Err(e) => e.code(),
+        }
+    });
+    if let Ok(code) = result {
+        code
+    } else {
+        Error::Unknown.code()
+    }
+}
+
+#[no_mangle]
+#[allow(clippy::not_unsafe_ptr_arg_deref)]
+pub extern "C" fn __precompile_smt_verify_gas(data_ptr: *const u8, data_len: usize) -> u64 {
+    let data = unsafe { slice::from_raw_parts(data_ptr, data_len) };
+
+    // the leaf plus one compression per level
+    let depth = utils::abi_array_len(data, 3).unwrap_or(0) as u64;
+
+    (depth + 1) * ANEMOI_EVAL
+}
+
+#[no_mangle]
+#[allow(clippy::not_unsafe_ptr_arg_deref)]
+pub extern "C" fn __precompile_smt_update(
+    data_ptr: *const u8,
+    data_len: usize,
+    ret_val: *mut u8,
+) -> u8 {
+    let result = panic::catch_unwind(|| {
+        let data = unsafe { slice::from_raw_parts(data_ptr, data_len) };
+        let ret = unsafe { slice::from_raw_parts_mut(ret_val, 32) };
+        match smt_update(data, ret) {
+            Ok(()) => 0,
+            Err(e) => e.code(),
+        }
+    });
+    if let Ok(code) = result {
+        code
+    } else {
+        Error::Unknown.code()
+    }
+}
+
+#[no_mangle]
+#[allow(clippy::not_unsafe_ptr_arg_deref)]
+pub extern "C" fn __precompile_smt_update_gas(data_ptr: *const u8, data_len: usize) -> u64 {
+    let data = unsafe { slice::from_raw_parts(data_ptr, data_len) };
+
+    // the old and the new path are both hashed
+    let depth = utils::abi_array_len(data, 4).unwrap_or(0) as u64;
+
+    2 * (depth + 1) * ANEMOI_EVAL
+}
+
+/// Root of a sparse tree whose slot for `key` holds `value`, `0` for an empty slot.
+///
+/// The slot is given by the low `siblings.len()` bits of `key`, bit `i` set
+/// means the node at level `i` is a right child. `siblings` go from the leaf
+/// to the root, and `key` must fit in the tree.
+pub fn smt_root(key: &Fr, value: &Fr, siblings: &[Fr]) -> Result<Fr> {
+    if siblings.len() > MAX_SMT_DEPTH {
+        return Err(Error::UnsupportedSize);
+    }
+    let bits = key.into_bigint().to_bits_le();
+    if bits[siblings.len()..].iter().any(|b| *b) {
+        return Err(Error::UnsupportedSize);
+    }
+
+    let mut current = if value.is_zero() {
+        Fr::zero()
+    } else {
+        smt_leaf(key, value)
+    };
+    for (i, sibling) in siblings.iter().enumerate() {
+        current = if bits[i] {
+            compress(sibling, &current)
+        } else {
+            compress(&current, sibling)
+        };
+    }
+
+    Ok(current)
+}
+
+fn into_canonical(tk: Option<Token>) -> Result<Fr> {
+    let v = utils::into_bytes32(tk).ok_or(Error::Deserialize)?;
+    utils::into_field(U256::from_big_endian(&v))
+}
+
+fn into_canonical_array(tk: Option<Token>) -> Result<Vec<Fr>> {
+    tk.and_then(|v| v.into_array())
+        .ok_or(Error::Deserialize)?
+        .into_iter()
+        .map(|v| into_canonical(Some(v)))
+        .collect()
+}
+
+/// Verify an inclusion proof of `(key, value)`, or an exclusion proof of `key` when `value` is `0`.
+fn smt_verify(data: &[u8]) -> Result<()> {
+    let rs = ethabi::decode(
+        &[
+            ParamType::FixedBytes(32),
+            ParamType::Uint(256),
+            ParamType::FixedBytes(32),
+            ParamType::Array(Box::new(ParamType::FixedBytes(32))),
+        ],
+        data,
+    )
+    .map_err(|_| Error::Deserialize)?;
+    let root = into_canonical(rs.first().cloned())?;
+    let key = utils::into_uint256(rs.get(1).cloned()).ok_or(Error::Deserialize)?;
+    let key = utils::into_field(key)?;
+    let value = into_canonical(rs.get(2).cloned())?;
+    let siblings = into_canonical_array(rs.get(3).cloned())?;
+
+    if smt_root(&key, &value, &siblings)? != root {
+        return Err(Error::VerifyFail);
+    }
+
+    Ok(())
+}
+
+/// Check the old value of `key` against `root` and return the root holding the new value.
+fn smt_update(data: &[u8], ret: &mut [u8]) -> Result<()> {
+    let rs = ethabi::decode(
+        &[
+            ParamType::FixedBytes(32),
+            ParamType::Uint(256),
+            ParamType::FixedBytes(32),
+            ParamType::FixedBytes(32),
+            ParamType::Array(Box::new(ParamType::FixedBytes(32))),
+        ],
+        data,
+    )
+    .map_err(|_| Error::Deserialize)?;
+    let root = into_canonical(rs.first().cloned())?;
+    let key = utils::into_uint256(rs.get(1).cloned()).ok_or(Error::Deserialize)?;
+    let key = utils::into_field(key)?;
+    let old_value = into_canonical(rs.get(2).cloned())?;
+    let new_value = into_canonical(rs.get(3).cloned())?;
+    let siblings = into_canonical_array(rs.get(4).cloned())?;
+
+    // the old and new paths share the siblings but hash independently
+    let roots = utils::par_try_map(&[old_value, new_value], |value| {
+        smt_root(&key, value, &siblings)
+    })?;
+    if roots[0] != root {
+        return Err(Error::VerifyFail);
+    }
+    let new_root = roots[1];
+
+    ret.copy_from_slice(&new_root.into_bigint().to_bytes_be());
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ark_ff::{vec, MontFp, One, UniformRand};
+    use ark_std::rand::SeedableRng;
+    use rand_chacha::ChaChaRng;
+    use uzkge::plonk::constraint_system::TurboCS;
+
+    fn full_root(leaves: &[Fr], depth: usize) -> Fr {
+        let mut level = leaves.to_vec();
+        level.resize(1 << depth, Fr::zero());
+        for _ in 0..depth {
+            level = level.chunks(2).map(|c| compress(&c[0], &c[1])).collect();
+        }
+        level[0]
+    }
+
+    #[test]
+    fn merkle_nodes_match_sponge_gadget() {
+        let mut prng = ChaChaRng::from_seed([0u8; 32]);
+        let left = Fr::rand(&mut prng);
+        let right = Fr::rand(&mut prng);
+
+        let mut cs = TurboCS::new();
+        cs.load_anemoi_parameters::<AnemoiJive254>();
+        let left_var = cs.new_variable(left);
+        let right_var = cs.new_variable(right);
+        let trace = AnemoiJive254::eval_variable_length_hash_with_trace(&[left, right]);
+        let out_var = cs.new_variable(compress(&left, &right));
+        cs.anemoi_variable_length_hash::<AnemoiJive254>(&trace, &[left_var, right_var], out_var);
+
+        let witness = cs.get_and_clear_witness();
+        cs.verify_witness(&witness, &[]).unwrap();
+
+        let mut bad = witness.clone();
+        bad[out_var] += Fr::one();
+        assert!(cs.verify_witness(&bad, &[]).is_err());
+
+        // a leaf is the same gadget on [key, value, zero]
+        let key = Fr::rand(&mut prng);
+        let value = Fr::rand(&mut prng);
+        let mut cs = TurboCS::new();
+        cs.load_anemoi_parameters::<AnemoiJive254>();
+        let key_var = cs.new_variable(key);
+        let value_var = cs.new_variable(value);
+        let zero_var = cs.zero_var();
+        let trace = AnemoiJive254::eval_variable_length_hash_with_trace(&[key, value, Fr::zero()]);
+        let out_var = cs.new_variable(smt_leaf(&key, &value));
+        cs.anemoi_variable_length_hash::<AnemoiJive254>(
+            &trace,
+            &[key_var, value_var, zero_var],
+            out_var,
+        );
+
+        let witness = cs.get_and_clear_witness();
+        cs.verify_witness(&witness, &[]).unwrap();
+        assert_ne!(smt_leaf(&key, &value), compress(&key, &value));
+
+        let expected: Fr = MontFp!(
+            "12775984769767037774720027316534794155356162558679076448984558313803018563650"
+        );
+        assert_eq!(smt_leaf(&Fr::from(1u64), &Fr::from(2u64)), expected);
+
+        // compress(1, 2) and the root of an empty subtree of height 1
+        let expected: Fr =
+            MontFp!("3232176606050061886547214688109437424090823516261260072355765645378606977819");
+        assert_eq!(compress(&Fr::from(1u64), &Fr::from(2u64)), expected);
+        let expected: Fr = MontFp!(
+            "10809926402830967010292031871942911649253129549740728488129482592572605000148"
+        );
+        assert_eq!(empty_root(1), expected);
+    }
+
+    #[test]
+    fn merkle_append_works() {
+        let mut prng = ChaChaRng::from_seed([0u8; 32]);
+        let depth = 4;
+        let leaves: Vec<Fr> = (0..6).map(|_| Fr::rand(&mut prng)).collect();
+
+        assert_eq!(empty_root(depth), full_root(&[], depth));
+
+        let mut frontier = vec![Fr::zero(); depth];
+        for (i, leaf) in leaves.iter().enumerate() {
+            let encode = |v: &[Fr]| {
+                Token::Array(
+                    v.iter()
+                        .map(|f| Token::FixedBytes(f.into_bigint().to_bytes_be()))
+                        .collect(),
+                )
+            };
+            let data = ethabi::encode(&[
+                encode(&frontier),
+                Token::Uint(i.into()),
+                Token::FixedBytes(leaf.into_bigint().to_bytes_be()),
+            ]);
+
+            let mut ret = vec![0u8; append_output_len(depth)];
+            eval_merkle_append(&data, &mut ret).unwrap();
+
+            let rs = ethabi::decode(
+                &[
+                    ParamType::Array(Box::new(ParamType::FixedBytes(32))),
+                    ParamType::FixedBytes(32),
+                ],
+                &ret,
+            )
+            .unwrap();
+            frontier = into_field_array(rs.first().cloned()).unwrap();
+            let root = utils::into_bytes32(rs.get(1).cloned()).unwrap();
+
+            let expected = full_root(&leaves[..=i], depth);
+            assert_eq!(root, expected.into_bigint().to_bytes_be()[..]);
+        }
+
+        // the tree is full after 2^depth leaves
+        assert!(matches!(
+            merkle_append(&mut frontier, 1 << depth, Fr::zero()),
+            Err(Error::UnsupportedSize)
+        ));
+    }
+
+    fn smt_tree(entries: &[(u64, Fr)], depth: usize) -> Vec<Vec<Fr>> {
+        let mut level = vec![Fr::zero(); 1 << depth];
+        for (k, v) in entries {
+            level[*k as usize] = smt_leaf(&Fr::from(*k), v);
+        }
+        let mut levels = vec![level];
+        for _ in 0..depth {
+            let next = levels
+                .last()
+                .unwrap()
+                .chunks(2)
+                .map(|c| compress(&c[0], &c[1]))
+                .collect();
+            levels.push(next);
+        }
+        levels
+    }
+
+    fn smt_siblings(levels: &[Vec<Fr>], key: u64) -> Vec<Fr> {
+        (0..levels.len() - 1)
+            .map(|i| levels[i][((key >> i) ^ 1) as usize])
+            .collect()
+    }
+
+    #[test]
+    fn smt_works() {
+        let mut prng = ChaChaRng::from_seed([0u8; 32]);
+        let depth = 8;
+        let entries = vec![(3u64, Fr::rand(&mut prng)), (200u64, Fr::rand(&mut prng))];
+        let levels = smt_tree(&entries, depth);
+        let root = levels[depth][0];
+
+        let word = |f: &Fr| Token::FixedBytes(f.into_bigint().to_bytes_be());
+        let proof = |key: u64, value: &Fr, siblings: &[Fr]| {
+            ethabi::encode(&[
+                word(&root),
+                Token::Uint(key.into()),
+                word(value),
+                Token::Array(siblings.iter().map(word).collect()),
+            ])
+        };
+
+        // inclusion
+        let siblings = smt_siblings(&levels, 3);
+        smt_verify(&proof(3, &entries[0].1, &siblings)).unwrap();
+        assert!(matches!(
+            smt_verify(&proof(3, &Fr::one(), &siblings)),
+            Err(Error::VerifyFail)
+        ));
+
+        // exclusion
+        let siblings = smt_siblings(&levels, 77);
+        smt_verify(&proof(77, &Fr::zero(), &siblings)).unwrap();
+        assert!(matches!(
+            smt_verify(&proof(3, &Fr::zero(), &smt_siblings(&levels, 3))),
+            Err(Error::VerifyFail)
+        ));
+
+        // siblings must be canonical, like the root and the value
+        let data = ethabi::encode(&[
+            word(&root),
+            Token::Uint(77u64.into()),
+            word(&Fr::zero()),
+            Token::Array(vec![Token::FixedBytes(vec![0xff; 32]); depth]),
+        ]);
+        assert!(matches!(smt_verify(&data), Err(Error::Deserialize)));
+
+        // keys must fit in the tree
+        assert!(matches!(
+            smt_verify(&proof(1 << depth, &Fr::zero(), &siblings)),
+            Err(Error::UnsupportedSize)
+        ));
+
+        // insert at 77
+        let value = Fr::rand(&mut prng);
+        let data = ethabi::encode(&[
+            word(&root),
+            Token::Uint(77u64.into()),
+            word(&Fr::zero()),
+            word(&value),
+            Token::Array(siblings.iter().map(word).collect()),
+        ]);
+        let mut ret = vec![0u8; 32];
+        smt_update(&data, &mut ret).unwrap();
+
+        let mut updated = entries.clone();
+        updated.push((77, value));
+        let expected = smt_tree(&updated, depth)[depth][0];
+        assert_eq!(ret, expected.into_bigint().to_bytes_be());
+
+        // a wrong old value is rejected even though the new path hashes fine
+        let data = ethabi::encode(&[
+            word(&root),
+            Token::Uint(77u64.into()),
+            word(&value),
+            word(&value),
+            Token::Array(siblings.iter().map(word).collect()),
+        ]);
+        assert!(matches!(
+            smt_update(&data, &mut ret),
+            Err(Error::VerifyFail)
+        ));
+    }
+}
@@ -0,0 +1,254 @@
+use alloc::vec::Vec;
+use ark_bn254::{Fr, G1Projective};
+use ark_serialize::{CanonicalDeserialize, CanonicalSerialize};
+use uzkge::{
+    gen_params::VerifierParams,
+    plonk::{
+        constraint_system::{ConstraintSystem, TurboCS},
+        indexer::PlonkVerifierParams,
+    },
+    poly_commit::kzg_poly_commitment::{KZGCommitment, KZGCommitmentSchemeBN254},
+};
+
+use crate::{Error, Result};
+
+// Canonical encoding of `VerifierParams`, independent of Rust struct layout:
+//
+// | section count (u32 BE) = 3 |
+// | length (u32 BE) | shrunk_vk       |
+// | length (u32 BE) | shrunk_cs       |
+// | length (u32 BE) | verifier_params |
+//
+// Inside a section, integers are u64 BE, lists are a u32 BE count followed by
+// their items, and curve points and field elements use their `ark_serialize`
+// compressed encoding, so points are checked to be on the curve when decoding.
+//
+// shrunk_vk:       G1 powers (list), G2 powers (list)
+// shrunk_cs:       n_iteration_shuffle_scalar_mul, num_vars, size
+// verifier_params: cm_q_vec (list), cm_s_vec (list), cm_qb, cm_prk_vec (list),
+//                  cm_q_ecc, cm_shuffle_generator_vec (list),
+//                  cm_shuffle_public_key_vec (list), anemoi_generator,
+//                  anemoi_generator_inv, k (list), edwards_a, cs_size,
+//                  public_vars_constraint_indices (list of integers),
+//                  lagrange_constants (list)
+//
+// Only a verifier-only constraint system can be encoded, since everything
+// else in it is rebuilt by `shrink_to_verifier_only`. Trailing bytes after the
+// last section, or inside a section, are rejected.
+
+pub const PARAMS_SECTIONS: u32 = 3;
+
+fn write_u32(v: &mut Vec<u8>, n: usize) -> Result<()> {
+    let n = u32::try_from(n).map_err(|_| Error::Serialize)?;
+    v.extend_from_slice(&n.to_be_bytes());
+    Ok(())
+}
+
+fn write_u64(v: &mut Vec<u8>, n: usize) {
+    v.extend_from_slice(&(n as u64).to_be_bytes());
+}
+
+fn write_item<T: CanonicalSerialize>(v: &mut Vec<u8>, t: &T) -> Result<()> {
+    t.serialize_compressed(&mut *v)
+        .map_err(|_| Error::Serialize)
+}
+
+fn write_list<T>(
+    v: &mut Vec<u8>,
+    items: &[T],
+    write: impl Fn(&mut Vec<u8>, &T) -> Result<()>,
+) -> Result<()> {
+    write_u32(v, items.len())?;
+    for item in items {
+        write(v, item)?;
+    }
+    Ok(())
+}
+
+fn write_commitment(v: &mut Vec<u8>, cm: &KZGCommitment<G1Projective>) -> Result<()> {
+    write_item(v, &cm.0)
+}
+
+fn write_section(v: &mut Vec<u8>, section: &[u8]) -> Result<()> {
+    write_u32(v, section.len())?;
+    v.extend_from_slice(section);
+    Ok(())
+}
+
+fn read_bytes<'a>(bytes: &mut &'a [u8], n: usize) -> Result<&'a [u8]> {
+    let b = bytes.get(..n).ok_or(Error::Deserialize)?;
+    *bytes = &bytes[n..];
+    Ok(b)
+}
+
+fn read_u32(bytes: &mut &[u8]) -> Result<usize> {
+    let b = read_bytes(bytes, 4)?;
+    Ok(u32::from_be_bytes(b.try_into().map_err(|_| Error::Deserialize)?) as usize)
+}
+
+fn read_u64(bytes: &mut &[u8]) -> Result<usize> {
+    let b = read_bytes(bytes, 8)?;
+    usize::try_from(u64::from_be_bytes(
+        b.try_into().map_err(|_| Error::Deserialize)?,
+    ))
+    .map_err(|_| Error::Deserialize)
+}
+
+fn read_item<T: CanonicalDeserialize>(bytes: &mut &[u8]) -> Result<T> {
+    T::deserialize_compressed(&mut *bytes).map_err(|_| Error::Deserialize)
+}
+
+fn read_list<T>(bytes: &mut &[u8], read: impl Fn(&mut &[u8]) -> Result<T>) -> Result<Vec<T>> {
+    let n = read_u32(bytes)?;
+    // every item takes at least one byte, so a bogus count fails before allocating much
+    let mut items = Vec::with_capacity(n.min(bytes.len()));
+    for _ in 0..n {
+        items.push(read(bytes)?);
+    }
+    Ok(items)
+}
+
+fn read_commitment(bytes: &mut &[u8]) -> Result<KZGCommitment<G1Projective>> {
+    read_item(bytes).map(KZGCommitment)
+}
+
+/// Split off the next section, checking that `read` consumes all of it.
+fn read_section<T>(bytes: &mut &[u8], read: impl Fn(&mut &[u8]) -> Result<T>) -> Result<T> {
+    let len = read_u32(bytes)?;
+    let mut section = read_bytes(bytes, len)?;
+    let t = read(&mut section)?;
+    if !section.is_empty() {
+        return Err(Error::Deserialize);
+    }
+    Ok(t)
+}
+
+/// Encode verifier params in the canonical format.
+pub fn to_canonical_bytes(params: &VerifierParams) -> Result<Vec<u8>> {
+    let vk = &params.shrunk_vk;
+    let cs = &params.shrunk_cs;
+    let vp = &params.verifier_params;
+    if !cs.verifier_only {
+        return Err(Error::Serialize);
+    }
+
+    let mut v = Vec::new();
+    write_u32(&mut v, PARAMS_SECTIONS as usize)?;
+
+    let mut section = Vec::new();
+    write_list(&mut section, &vk.public_parameter_group_1, write_item)?;
+    write_list(&mut section, &vk.public_parameter_group_2, write_item)?;
+    write_section(&mut v, &section)?;
+
+    let mut section = Vec::new();
+    write_u64(&mut section, cs.n_iteration_shuffle_scalar_mul);
+    write_u64(&mut section, cs.num_vars);
+    write_u64(&mut section, cs.size);
+    write_section(&mut v, &section)?;
+
+    let mut section = Vec::new();
+    write_list(&mut section, &vp.cm_q_vec, write_commitment)?;
+    write_list(&mut section, &vp.cm_s_vec, write_commitment)?;
+    write_commitment(&mut section, &vp.cm_qb)?;
+    write_list(&mut section, &vp.cm_prk_vec, write_commitment)?;
+    write_commitment(&mut section, &vp.cm_q_ecc)?;
+    write_list(&mut section, &vp.cm_shuffle_generator_vec, write_commitment)?;
+    write_list(
+        &mut section,
+        &vp.cm_shuffle_public_key_vec,
+        write_commitment,
+    )?;
+    write_item(&mut section, &vp.anemoi_generator)?;
+    write_item(&mut section, &vp.anemoi_generator_inv)?;
+    write_list(&mut section, &vp.k, write_item)?;
+    write_item(&mut section, &vp.edwards_a)?;
+    write_u64(&mut section, vp.cs_size);
+    write_list(&mut section, &vp.public_vars_constraint_indices, |v, i| {
+        write_u64(v, *i);
+        Ok(())
+    })?;
+    write_list(&mut section, &vp.lagrange_constants, write_item)?;
+    write_section(&mut v, &section)?;
+
+    Ok(v)
+}
+
+/// Decode verifier params from the canonical format.
+pub fn from_canonical_bytes(mut bytes: &[u8]) -> Result<VerifierParams> {
+    if read_u32(&mut bytes)? != PARAMS_SECTIONS as usize {
+        return Err(Error::Deserialize);
+    }
+
+    let shrunk_vk = read_section(&mut bytes, |b| {
+        Ok(KZGCommitmentSchemeBN254 {
+            public_parameter_group_1: read_list(b, read_item)?,
+            public_parameter_group_2: read_list(b, read_item)?,
+        })
+    })?;
+
+    let shrunk_cs = read_section(&mut bytes, |b| {
+        let mut cs = TurboCS::<Fr>::new();
+        cs.n_iteration_shuffle_scalar_mul = read_u64(b)?;
+        cs.num_vars = read_u64(b)?;
+        cs.size = read_u64(b)?;
+        Ok(cs.shrink_to_verifier_only())
+    })?;
+
+    let verifier_params = read_section(&mut bytes, |b| {
+        Ok(PlonkVerifierParams {
+            cm_q_vec: read_list(b, read_commitment)?,
+            cm_s_vec: read_list(b, read_commitment)?,
+            cm_qb: read_commitment(b)?,
+            cm_prk_vec: read_list(b, read_commitment)?,
+            cm_q_ecc: read_commitment(b)?,
+            cm_shuffle_generator_vec: read_list(b, read_commitment)?,
+            cm_shuffle_public_key_vec: read_list(b, read_commitment)?,
+            anemoi_generator: read_item(b)?,
+            anemoi_generator_inv: read_item(b)?,
+            k: read_list(b, read_item)?,
+            edwards_a: read_item(b)?,
+            cs_size: read_u64(b)?,
+            public_vars_constraint_indices: read_list(b, read_u64)?,
+            lagrange_constants: read_list(b, read_item)?,
+        })
+    })?;
+
+    if !bytes.is_empty() {
+        return Err(Error::Deserialize);
+    }
+
+    Ok(VerifierParams {
+        shrunk_vk,
+        shrunk_cs,
+        verifier_params,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use zmatchmaking::gen_params::get_verifier_params;
+    use zshuffle::gen_params::get_shuffle_verifier_params;
+
+    #[test]
+    fn canonical_params_round_trip() {
+        for params in [
+            get_verifier_params().unwrap(),
+            get_shuffle_verifier_params(52).unwrap(),
+        ] {
+            let bytes = to_canonical_bytes(&params).unwrap();
+            let decoded = from_canonical_bytes(&bytes).unwrap();
+
+            assert_eq!(
+                bincode::serialize(&decoded).unwrap(),
+                bincode::serialize(&params).unwrap()
+            );
+
+            // truncated and padded inputs are rejected
+            assert!(from_canonical_bytes(&bytes[..bytes.len() - 1]).is_err());
+            let mut padded = bytes.clone();
+            padded.push(0);
+            assert!(from_canonical_bytes(&padded).is_err());
+        }
+    }
+}
@@ -1,20 +1,64 @@
 use alloc::{boxed::Box, vec::Vec};
 use ark_bn254::Fr;
-use ark_ed_on_bn254::{EdwardsAffine, EdwardsProjective, Fq};
+use ark_ec::AffineRepr;
 use ark_ff::PrimeField;
 use ark_std::panic;
 use core::slice;
-use ethabi::ParamType;
-use uzkge::gen_params::VerifierParams;
-use zmatchmaking::build_cs::{verify_matchmaking, Proof};
+use ethabi::{ParamType, Token};
+use lazy_static::lazy_static;
+use primitive_types::U256;
+use uzkge::{
+    anemoi::{AnemoiJive, AnemoiJive254},
+    gen_params::VerifierParams,
+    plonk::{indexer::PlonkProof, verifier::verifier},
+    poly_commit::kzg_poly_commitment::KZGCommitmentSchemeBN254,
+    utils::transcript::Transcript,
+};
+use zmatchmaking::{
+    build_cs::{verify_matchmaking, Proof, N},
+    gen_params::get_verifier_params,
+};
 use zshuffle::{
     build_cs::{verify_shuffle, ShuffleProof, TurboCS},
     MaskedCard,
 };
 
-use crate::{utils, Error, Result};
+use crate::{
+    anemoi::ANEMOI_EVAL,
+    ed_on_bn254::into_point,
+    envelope::{self, Circuit, Curve, Encoding},
+    params, utils, Error, Result,
+};
 
 pub const PLONL_VERIFY_BASE: u64 = 100;
+pub const PLONK_VERIFY_PER_INPUT: u64 = 10;
+pub const PLONK_VERIFY_PER_PARAMS_WORD: u64 = 1;
+
+type ParamsLoader = fn() -> Option<VerifierParams>;
+
+/// Loaders of the built-in matchmaking verifier params, keyed by the number of players.
+/// zmatchmaking only publishes params for its compiled circuit size `N`, other sizes
+/// are registered here as their params are released.
+const MATCHMAKING_PARAM_LOADERS: &[(usize, ParamsLoader)] = &[(N, || get_verifier_params().ok())];
+
+lazy_static! {
+    /// Built-in matchmaking verifier params. An entry whose params fail to load is kept
+    /// as `None` and rejected on use, instead of poisoning the static with a panic.
+    static ref MATCHMAKING_PARAMS: Vec<(usize, Option<VerifierParams>)> = MATCHMAKING_PARAM_LOADERS
+        .iter()
+        .map(|(n, load)| (*n, load()))
+        .collect();
+}
+
+/// Deck sizes accepted by `__precompile_verify_shuffle`.
+pub const SHUFFLE_DECK_SIZES: [usize; 6] = [20, 32, 48, 52, 54, 104];
+
+/// Each deck position makes its input and output card public as `e1.x, e1.y, e2.x, e2.y`.
+const SHUFFLE_PUBLIC_INPUTS_PER_CARD: usize = 8;
+
+/// Transcript labels accepted by `__precompile_plonk_verify`. A uzkge prover must start
+/// from `Transcript::new(label)` with one of these, and pass the same label in call data.
+pub const PLONK_TRANSCRIPT_LABELS: &[&[u8]] = &[b"Zytron Plonk"];
 
 #[no_mangle]
 #[allow(clippy::not_unsafe_ptr_arg_deref)]
@@ -34,6 +78,54 @@ pub extern "C" fn __precompile_verify_matchmaking(data_ptr: *const u8, data_len:
     }
 }
 
+#[no_mangle]
+#[allow(clippy::not_unsafe_ptr_arg_deref)]
+pub extern "C" fn __precompile_verify_matchmaking_with_seed(
+    data_ptr: *const u8,
+    data_len: usize,
+) -> u8 {
+    let result = panic::catch_unwind(|| {
+        let data = unsafe { slice::from_raw_parts(data_ptr, data_len) };
+
+        match plonk_verify_matchmaking_with_seed(data) {
+            Ok(()) => 0,
+            Err(e) => e.code(),
+        }
+    });
+    if let Ok(code) = result {
+        code
+    } else {
+        Error::Unknown.code()
+    }
+}
+
+#[no_mangle]
+#[allow(clippy::not_unsafe_ptr_arg_deref)]
+pub extern "C" fn __precompile_verify_matchmaking_with_seed_gas(_: *const u8, _: usize) -> u64 {
+    PLONL_VERIFY_BASE + ANEMOI_EVAL
+}
+
+#[no_mangle]
+#[allow(clippy::not_unsafe_ptr_arg_deref)]
+pub extern "C" fn __precompile_verify_matchmaking_sized(
+    data_ptr: *const u8,
+    data_len: usize,
+) -> u8 {
+    let result = panic::catch_unwind(|| {
+        let data = unsafe { slice::from_raw_parts(data_ptr, data_len) };
+
+        match plonk_verify_matchmaking_sized(data) {
+            Ok(()) => 0,
+            Err(e) => e.code(),
+        }
+    });
+    if let Ok(code) = result {
+        code
+    } else {
+        Error::Unknown.code()
+    }
+}
+
 #[no_mangle]
 #[allow(clippy::not_unsafe_ptr_arg_deref)]
 pub extern "C" fn __precompile_verify_shuffle(data_ptr: *const u8, data_len: usize) -> u8 {
@@ -52,6 +144,53 @@ pub extern "C" fn __precompile_verify_shuffle(data_ptr: *const u8, data_len: usi
     }
 }
 
+/// Same as `__precompile_verify_shuffle`, also writing `(uint256 code, uint256 index)`
+/// abi encoded to `ret_val`, where `index` is the malformed card when `code` is 9.
+#[no_mangle]
+#[allow(clippy::not_unsafe_ptr_arg_deref)]
+pub extern "C" fn __precompile_verify_shuffle_detailed(
+    data_ptr: *const u8,
+    data_len: usize,
+    ret_val: *mut u8,
+) -> u8 {
+    let ret = unsafe { slice::from_raw_parts_mut(ret_val, 64) };
+    ret.fill(0);
+
+    let result = panic::catch_unwind(|| {
+        let data = unsafe { slice::from_raw_parts(data_ptr, data_len) };
+
+        plonk_verify_shuffle(data)
+    });
+    let (code, index) = match result {
+        Ok(Ok(())) => (0, 0),
+        Ok(Err(Error::InvalidCard(i))) => (Error::InvalidCard(i).code(), i),
+        Ok(Err(e)) => (e.code(), 0),
+        Err(_) => (Error::Unknown.code(), 0),
+    };
+    ret[31] = code;
+    U256::from(index).to_big_endian(&mut ret[32..]);
+
+    code
+}
+
+#[no_mangle]
+#[allow(clippy::not_unsafe_ptr_arg_deref)]
+pub extern "C" fn __precompile_plonk_verify(data_ptr: *const u8, data_len: usize) -> u8 {
+    let result = panic::catch_unwind(|| {
+        let data = unsafe { slice::from_raw_parts(data_ptr, data_len) };
+
+        match plonk_verify(data) {
+            Ok(()) => 0,
+            Err(e) => e.code(),
+        }
+    });
+    if let Ok(code) = result {
+        code
+    } else {
+        Error::Unknown.code()
+    }
+}
+
 #[no_mangle]
 #[allow(clippy::not_unsafe_ptr_arg_deref)]
 pub extern "C" fn __precompile_plonk_verify_gas(_data_ptr: *const u8, _data_len: usize) -> u64 {
@@ -60,6 +199,82 @@ pub extern "C" fn __precompile_plonk_verify_gas(_data_ptr: *const u8, _data_len:
     PLONL_VERIFY_BASE
 }
 
+/// Gas of `__precompile_verify_matchmaking_sized`, growing with the player count since
+/// every player adds an input and an output to the public inputs.
+#[no_mangle]
+#[allow(clippy::not_unsafe_ptr_arg_deref)]
+pub extern "C" fn __precompile_plonk_verify_matchmaking_sized_gas(
+    data_ptr: *const u8,
+    data_len: usize,
+) -> u64 {
+    let data = unsafe { slice::from_raw_parts(data_ptr, data_len) };
+
+    // unsupported sizes fail before verifying, so they only pay the base
+    let players = data
+        .get(..32)
+        .map(U256::from_big_endian)
+        .and_then(|p| {
+            MATCHMAKING_PARAM_LOADERS
+                .iter()
+                .find(|(n, _)| p == U256::from(*n))
+        })
+        .map(|(n, _)| *n as u64)
+        .unwrap_or(0);
+
+    PLONL_VERIFY_BASE + 2 * players * PLONK_VERIFY_PER_INPUT
+}
+
+/// Gas of `__precompile_plonk_verify`, which unlike the fixed circuits takes params and
+/// public inputs of any size.
+#[no_mangle]
+#[allow(clippy::not_unsafe_ptr_arg_deref)]
+pub extern "C" fn __precompile_plonk_verify_generic_gas(
+    data_ptr: *const u8,
+    data_len: usize,
+) -> u64 {
+    let data = unsafe { slice::from_raw_parts(data_ptr, data_len) };
+
+    // decoding the params grows with their size, the verifier with the public inputs
+    let params_words = utils::abi_array_len(data, 0).unwrap_or(0).div_ceil(32) as u64;
+    let num_inputs = utils::abi_array_len(data, 1).unwrap_or(0) as u64;
+
+    PLONL_VERIFY_BASE
+        + num_inputs * PLONK_VERIFY_PER_INPUT
+        + params_words * PLONK_VERIFY_PER_PARAMS_WORD
+}
+
+/// Decode verifier params, either legacy bincode bytes, raw canonical bytes or an
+/// envelope for `circuit` holding bincode or canonical bytes.
+fn into_verifier_params(bytes: &[u8], circuit: Circuit) -> Result<VerifierParams> {
+    // raw canonical bytes are accepted without an envelope. bincode starts with the
+    // little-endian `u64` length of the first vk list, which never reads as the
+    // canonical section count, so bytes with that prefix are only tried as canonical.
+    if !bytes.starts_with(&envelope::ENVELOPE_MAGIC)
+        && bytes.starts_with(&params::PARAMS_SECTIONS.to_be_bytes())
+    {
+        return params::from_canonical_bytes(bytes);
+    }
+
+    match envelope::open(bytes, Curve::Bn254, circuit, Encoding::Bincode)? {
+        (Encoding::Bincode, v) => bincode::deserialize(v).map_err(|_e| Error::Deserialize),
+        (Encoding::Canonical, v) => params::from_canonical_bytes(v),
+        _ => Err(Error::Deserialize),
+    }
+}
+
+/// Decode a proof, either legacy bytes or an envelope for `circuit`, both in `encoding`.
+fn into_proof<T, E>(
+    bytes: &[u8],
+    circuit: Circuit,
+    encoding: Encoding,
+    decode: impl FnOnce(&[u8]) -> core::result::Result<T, E>,
+) -> Result<T> {
+    match envelope::open(bytes, Curve::Bn254, circuit, encoding)? {
+        (e, v) if e == encoding => decode(v).map_err(|_e| Error::Deserialize),
+        _ => Err(Error::Deserialize),
+    }
+}
+
 fn plonk_verify_matchmaking(data: &[u8]) -> Result<()> {
     let r = ethabi::decode(
         &[
@@ -74,66 +289,206 @@ fn plonk_verify_matchmaking(data: &[u8]) -> Result<()> {
     )
     .map_err(|_| Error::Deserialize)?;
 
-    let verifier_params: VerifierParams = utils::into_bytes(r.first().cloned())
+    let verifier_params = utils::into_bytes(r.first().cloned())
         .ok_or(Error::Deserialize)
-        .and_then(|v| bincode::deserialize(&v).map_err(|_e| Error::Deserialize))?;
+        .and_then(|v| into_verifier_params(&v, Circuit::Matchmaking))?;
 
-    let inputs = utils::into_bytes_array(r.get(1).cloned())
-        .map(|is| {
-            is.iter()
-                .map(|v| Fr::from_be_bytes_mod_order(v))
-                .collect::<Vec<_>>()
-        })
-        .ok_or(Error::Deserialize)?;
+    Matchmaking::from_tokens(r.get(1..).ok_or(Error::Deserialize)?)?.verify(&verifier_params)
+}
 
-    let outputs = utils::into_bytes_array(r.get(2).cloned())
-        .map(|is| {
-            is.iter()
-                .map(|v| Fr::from_be_bytes_mod_order(v))
-                .collect::<Vec<_>>()
-        })
-        .ok_or(Error::Deserialize)?;
+/// Verify a matchmaking proof against the commitment of a revealed seed.
+///
+/// Same layout as `plonk_verify_matchmaking` with the committed seed in place of the
+/// commitment, followed by `bytes commitment`, the commitment the caller expects. The
+/// Anemoi hash of the seed must equal it, otherwise this fails with `VerifyFail`.
+fn plonk_verify_matchmaking_with_seed(data: &[u8]) -> Result<()> {
+    let r = ethabi::decode(
+        &[
+            ParamType::Bytes,
+            ParamType::Array(Box::new(ParamType::Bytes)),
+            ParamType::Array(Box::new(ParamType::Bytes)),
+            ParamType::Bytes,
+            ParamType::Bytes,
+            ParamType::Bytes,
+            ParamType::Bytes,
+        ],
+        data,
+    )
+    .map_err(|_| Error::Deserialize)?;
 
-    let commitment = utils::into_bytes(r.get(3).cloned())
-        .map(|v| Fr::from_be_bytes_mod_order(&v))
-        .ok_or(Error::Deserialize)?;
+    let verifier_params = utils::into_bytes(r.first().cloned())
+        .ok_or(Error::Deserialize)
+        .and_then(|v| into_verifier_params(&v, Circuit::Matchmaking))?;
 
-    let random_number = utils::into_bytes(r.get(4).cloned())
+    let mut matchmaking = Matchmaking::from_tokens(r.get(1..6).ok_or(Error::Deserialize)?)?;
+    let expected = utils::into_bytes(r.get(6).cloned())
         .map(|v| Fr::from_be_bytes_mod_order(&v))
         .ok_or(Error::Deserialize)?;
 
-    let proof: Proof = utils::into_bytes(r.get(5).cloned())
-        .ok_or(Error::Deserialize)
-        .and_then(|v| bincode::deserialize(&v).map_err(|_e| Error::Deserialize))?;
-
-    verify_matchmaking(
-        &verifier_params,
-        &inputs,
-        &outputs,
-        &commitment,
-        &random_number,
-        &proof,
+    matchmaking.commitment = AnemoiJive254::eval_variable_length_hash(&[matchmaking.commitment]);
+    if matchmaking.commitment != expected {
+        return Err(Error::VerifyFail);
+    }
+
+    matchmaking.verify(&verifier_params)
+}
+
+/// Verify a matchmaking proof for a given number of players with the built-in params.
+///
+/// Layout is `(uint256 players, bytes[] inputs, bytes[] outputs, bytes commitment,
+/// bytes random_number, bytes proof)`. An unsupported player count fails with
+/// `UnsupportedSize`, and inputs or outputs of another length with `LengthMismatch`.
+fn plonk_verify_matchmaking_sized(data: &[u8]) -> Result<()> {
+    let r = ethabi::decode(
+        &[
+            ParamType::Uint(256),
+            ParamType::Array(Box::new(ParamType::Bytes)),
+            ParamType::Array(Box::new(ParamType::Bytes)),
+            ParamType::Bytes,
+            ParamType::Bytes,
+            ParamType::Bytes,
+        ],
+        data,
     )
-    .map_err(|_e| Error::VerifyFail)
+    .map_err(|_| Error::Deserialize)?;
+
+    let players = utils::into_uint256(r.first().cloned()).ok_or(Error::Deserialize)?;
+    let verifier_params = MATCHMAKING_PARAMS
+        .iter()
+        .find(|(n, _)| players == U256::from(*n))
+        .map(|(_, params)| params)
+        .ok_or(Error::UnsupportedSize)?
+        .as_ref()
+        .ok_or(Error::Deserialize)?;
+    let players = players.as_usize();
+
+    let matchmaking = Matchmaking::from_tokens(r.get(1..).ok_or(Error::Deserialize)?)?;
+    if matchmaking.inputs.len() != players || matchmaking.outputs.len() != players {
+        return Err(Error::LengthMismatch);
+    }
+
+    matchmaking.verify(verifier_params)
 }
 
-fn bytes_2_masked_card(cards: &[Vec<u8>]) -> Result<MaskedCard> {
-    let e1: EdwardsProjective = {
-        let x = Fq::from_be_bytes_mod_order(cards.first().ok_or(Error::Deserialize)?);
-        let y = Fq::from_be_bytes_mod_order(cards.get(1).ok_or(Error::Deserialize)?);
-        let affine = EdwardsAffine::new(x, y);
-        affine.into()
-    };
+/// The public inputs and proof of one matchmaking.
+struct Matchmaking {
+    inputs: Vec<Fr>,
+    outputs: Vec<Fr>,
+    commitment: Fr,
+    random_number: Fr,
+    proof: Proof,
+}
 
-    let e2: EdwardsProjective = {
-        let x = Fq::from_be_bytes_mod_order(cards.get(2).ok_or(Error::Deserialize)?);
-        let y = Fq::from_be_bytes_mod_order(cards.get(3).ok_or(Error::Deserialize)?);
-        let affine = EdwardsAffine::new(x, y);
-        affine.into()
-    };
+impl Matchmaking {
+    /// Decode from `(inputs, outputs, commitment, random_number, proof)` tokens.
+    fn from_tokens(r: &[Token]) -> Result<Self> {
+        let inputs = utils::into_bytes_array(r.first().cloned())
+            .map(|is| {
+                is.iter()
+                    .map(|v| Fr::from_be_bytes_mod_order(v))
+                    .collect::<Vec<_>>()
+            })
+            .ok_or(Error::Deserialize)?;
+
+        let outputs = utils::into_bytes_array(r.get(1).cloned())
+            .map(|is| {
+                is.iter()
+                    .map(|v| Fr::from_be_bytes_mod_order(v))
+                    .collect::<Vec<_>>()
+            })
+            .ok_or(Error::Deserialize)?;
+
+        let commitment = utils::into_bytes(r.get(2).cloned())
+            .map(|v| Fr::from_be_bytes_mod_order(&v))
+            .ok_or(Error::Deserialize)?;
+
+        let random_number = utils::into_bytes(r.get(3).cloned())
+            .map(|v| Fr::from_be_bytes_mod_order(&v))
+            .ok_or(Error::Deserialize)?;
+
+        let proof: Proof = utils::into_bytes(r.get(4).cloned())
+            .ok_or(Error::Deserialize)
+            .and_then(|v| {
+                into_proof(&v, Circuit::Matchmaking, Encoding::Bincode, |v| {
+                    bincode::deserialize(v)
+                })
+            })?;
+
+        Ok(Self {
+            inputs,
+            outputs,
+            commitment,
+            random_number,
+            proof,
+        })
+    }
+
+    fn verify(&self, verifier_params: &VerifierParams) -> Result<()> {
+        verify_matchmaking(
+            verifier_params,
+            &self.inputs,
+            &self.outputs,
+            &self.commitment,
+            &self.random_number,
+            &self.proof,
+        )
+        .map_err(|_e| Error::VerifyFail)
+    }
+}
+
+/// Decode a masked card from exactly four 32-byte canonical coordinates
+/// `[e1.x, e1.y, e2.x, e2.y]`, each pair a point in the prime subgroup.
+pub(crate) fn bytes_2_masked_card(cards: &[Vec<u8>]) -> Result<MaskedCard> {
+    if cards.len() != 4 {
+        return Err(Error::Deserialize);
+    }
+
+    let mut coords = [U256::zero(); 4];
+    for (coord, bytes) in coords.iter_mut().zip(cards) {
+        if bytes.len() != 32 {
+            return Err(Error::Deserialize);
+        }
+        *coord = U256::from_big_endian(bytes);
+    }
+
+    let e1 = into_point(coords[0], coords[1])?.into_group();
+    let e2 = into_point(coords[2], coords[3])?.into_group();
     Ok(MaskedCard { e1, e2 })
 }
 
+/// Decode a deck, reporting a malformed card as `InvalidCard(offset + index)`.
+fn into_deck(cards: &[Vec<Vec<u8>>], offset: usize) -> Result<Vec<MaskedCard>> {
+    let mut ret = Vec::with_capacity(cards.len());
+    for (i, card) in cards.iter().enumerate() {
+        ret.push(bytes_2_masked_card(card).map_err(|_| Error::InvalidCard(offset + i))?);
+    }
+    Ok(ret)
+}
+
+/// Check the decks against each other, the supported sizes and the params' circuit size.
+fn check_deck_size(
+    verifier_params: &VerifierParams,
+    num_inputs: usize,
+    num_outputs: usize,
+) -> Result<()> {
+    if num_inputs != num_outputs {
+        return Err(Error::LengthMismatch);
+    }
+    if !SHUFFLE_DECK_SIZES.contains(&num_inputs) {
+        return Err(Error::UnsupportedSize);
+    }
+
+    let num_public_inputs = verifier_params
+        .verifier_params
+        .public_vars_constraint_indices
+        .len();
+    if num_public_inputs != num_inputs * SHUFFLE_PUBLIC_INPUTS_PER_CARD {
+        return Err(Error::ParamsMismatch);
+    }
+
+    Ok(())
+}
+
 fn plonk_verify_shuffle(data: &[u8]) -> Result<()> {
     let r = ethabi::decode(
         &[
@@ -146,57 +501,116 @@ fn plonk_verify_shuffle(data: &[u8]) -> Result<()> {
     )
     .map_err(|_| Error::Deserialize)?;
 
-    let verifier_params: VerifierParams = utils::into_bytes(r.first().cloned())
+    let verifier_params = utils::into_bytes(r.first().cloned())
         .ok_or(Error::Deserialize)
-        .and_then(|v| bincode::deserialize(&v).map_err(|_e| Error::Deserialize))?;
+        .and_then(|v| into_verifier_params(&v, Circuit::Shuffle))?;
 
-    let input_cards = {
-        let cards = utils::into_bytes_2d_array(r.get(1).cloned()).ok_or(Error::Deserialize)?;
-        let mut ret = Vec::new();
-        for card in cards {
-            ret.push(bytes_2_masked_card(&card)?);
-        }
-        ret
-    };
+    let input_cards = utils::into_bytes_2d_array(r.get(1).cloned()).ok_or(Error::Deserialize)?;
+    let input_cards = into_deck(&input_cards, 0)?;
 
-    let output_cards = {
-        let cards = utils::into_bytes_2d_array(r.get(2).cloned()).ok_or(Error::Deserialize)?;
-        let mut ret = Vec::new();
-        for card in cards {
-            ret.push(bytes_2_masked_card(&card)?);
-        }
-        ret
-    };
+    let output_cards = utils::into_bytes_2d_array(r.get(2).cloned()).ok_or(Error::Deserialize)?;
+    let output_cards = into_deck(&output_cards, input_cards.len())?;
+
+    check_deck_size(&verifier_params, input_cards.len(), output_cards.len())?;
 
     let proof: ShuffleProof = utils::into_bytes(r.get(3).cloned())
         .ok_or(Error::Deserialize)
         .and_then(|v| {
-            ShuffleProof::from_bytes_be::<TurboCS>(&v).map_err(|_e| Error::Deserialize)
+            into_proof(&v, Circuit::Shuffle, Encoding::BytesBe, |v| {
+                ShuffleProof::from_bytes_be::<TurboCS>(v)
+            })
         })?;
 
     verify_shuffle(&verifier_params, &input_cards, &output_cards, &proof)
         .map_err(|_e| Error::VerifyFail)
 }
 
+/// Map a transcript label from call data to its registered static label.
+///
+/// uzkge's `Transcript::new` only takes `&'static` labels, so a label outside
+/// `PLONK_TRANSCRIPT_LABELS` cannot be reproduced and fails with `ParamsMismatch`.
+fn into_transcript_label(label: &[u8]) -> Result<&'static [u8]> {
+    PLONK_TRANSCRIPT_LABELS
+        .iter()
+        .find(|l| **l == label)
+        .copied()
+        .ok_or(Error::ParamsMismatch)
+}
+
+/// Verify a TurboPlonk proof of any uzkge circuit against its verifier params.
+///
+/// Layout is `(bytes params, uint256[] public_inputs, bytes proof, bytes label)`, where
+/// `label` is the transcript label the prover passed to `Transcript::new`.
+fn plonk_verify(data: &[u8]) -> Result<()> {
+    let r = ethabi::decode(
+        &[
+            ParamType::Bytes,
+            ParamType::Array(Box::new(ParamType::Uint(256))),
+            ParamType::Bytes,
+            ParamType::Bytes,
+        ],
+        data,
+    )
+    .map_err(|_| Error::Deserialize)?;
+
+    let verifier_params = utils::into_bytes(r.first().cloned())
+        .ok_or(Error::Deserialize)
+        .and_then(|v| into_verifier_params(&v, Circuit::Plonk))?;
+
+    let public_inputs = utils::into_uint256_array(r.get(1).cloned())
+        .ok_or(Error::Deserialize)?
+        .into_iter()
+        .map(utils::into_field)
+        .collect::<Result<Vec<Fr>>>()?;
+
+    let proof: PlonkProof<KZGCommitmentSchemeBN254> = utils::into_bytes(r.get(2).cloned())
+        .ok_or(Error::Deserialize)
+        .and_then(|v| {
+            into_proof(&v, Circuit::Plonk, Encoding::BytesBe, |v| {
+                PlonkProof::from_bytes_be::<TurboCS>(v)
+            })
+        })?;
+
+    let label = utils::into_bytes(r.get(3).cloned()).ok_or(Error::Deserialize)?;
+    let mut transcript = Transcript::new(into_transcript_label(&label)?);
+    verifier(
+        &mut transcript,
+        &verifier_params.shrunk_vk,
+        &verifier_params.shrunk_cs,
+        &verifier_params.verifier_params,
+        &public_inputs,
+        &proof,
+    )
+    .map_err(|_e| Error::VerifyFail)
+}
+
 #[cfg(test)]
 mod tests {
-    use alloc::vec::Vec;
+    use alloc::{vec, vec::Vec};
     use ark_bn254::Fr;
     use ark_ec::{AffineRepr, CurveGroup};
+    use ark_ed_on_bn254::EdwardsProjective;
     use ark_ff::{BigInteger, One, PrimeField, UniformRand};
     use ark_std::{
         collections::HashMap,
         rand::{CryptoRng, RngCore, SeedableRng},
     };
-    use ethabi::Token;
+    use ethabi::{ParamType, Token};
+    use primitive_types::U256;
     use rand_chacha::ChaChaRng;
-    use uzkge::anemoi::{AnemoiJive, AnemoiJive254};
+    use uzkge::{
+        anemoi::{AnemoiJive, AnemoiJive254},
+        gen_params::{ProverParams, VerifierParams},
+        plonk::{constraint_system::ConstraintSystem, indexer::indexer, prover::prover},
+        poly_commit::{kzg_poly_commitment::KZGCommitmentSchemeBN254, pcs::PolyComScheme},
+        utils::transcript::Transcript,
+    };
     use zmatchmaking::{
         build_cs::{prove_matchmaking, N},
         gen_params::{gen_prover_params, get_verifier_params},
     };
     use zshuffle::{
-        build_cs::prove_shuffle,
+        build_cs::{prove_shuffle, TurboCS},
         gen_params::{
             gen_shuffle_prover_params, get_shuffle_verifier_params,
             refresh_prover_params_public_key,
@@ -206,16 +620,25 @@ mod tests {
         Card,
     };
 
-    use super::{plonk_verify_matchmaking, plonk_verify_shuffle};
+    use crate::{
+        envelope::{seal, Circuit, Curve, Encoding},
+        params::to_canonical_bytes,
+        Error,
+    };
+
+    use super::{
+        __precompile_plonk_verify_matchmaking_sized_gas, __precompile_verify_matchmaking_sized,
+        __precompile_verify_shuffle, __precompile_verify_shuffle_detailed, check_deck_size,
+        plonk_verify, plonk_verify_matchmaking, plonk_verify_matchmaking_sized,
+        plonk_verify_matchmaking_with_seed, plonk_verify_shuffle, PLONK_TRANSCRIPT_LABELS,
+        PLONK_VERIFY_PER_INPUT, PLONL_VERIFY_BASE, SHUFFLE_DECK_SIZES,
+    };
 
     #[test]
     fn test_plonk_verify_matchmaking() {
         let mut rng = ChaChaRng::from_entropy();
 
-        let inputs = (1..=N)
-            .into_iter()
-            .map(|i| Fr::from(i as u64))
-            .collect::<Vec<_>>();
+        let inputs = (1..=N).map(|i| Fr::from(i as u64)).collect::<Vec<_>>();
 
         let committed_seed = Fr::rand(&mut rng);
 
@@ -262,6 +685,147 @@ mod tests {
         plonk_verify_matchmaking(&data).unwrap()
     }
 
+    fn matchmaking_tokens<R: CryptoRng + RngCore>(
+        rng: &mut R,
+        prover_params: &ProverParams,
+    ) -> (Vec<Token>, Fr) {
+        let inputs = (1..=N).map(|i| Fr::from(i as u64)).collect::<Vec<_>>();
+        let committed_seed = Fr::rand(rng);
+        let committment = AnemoiJive254::eval_variable_length_hash(&[committed_seed]);
+        let random_number = Fr::rand(rng);
+
+        let (proof, outputs) =
+            prove_matchmaking(rng, &inputs, &committed_seed, &random_number, prover_params)
+                .unwrap();
+
+        let to_bytes = |v: &Fr| Token::Bytes(v.into_bigint().to_bytes_be());
+
+        let tokens = vec![
+            Token::Array(inputs.iter().map(to_bytes).collect()),
+            Token::Array(outputs.iter().map(to_bytes).collect()),
+            to_bytes(&committment),
+            to_bytes(&random_number),
+            Token::Bytes(bincode::serialize(&proof).unwrap()),
+        ];
+
+        (tokens, committed_seed)
+    }
+
+    #[test]
+    fn test_plonk_verify_matchmaking_canonical_params() {
+        let mut rng = ChaChaRng::from_seed([0u8; 32]);
+        let prover_params = gen_prover_params().unwrap();
+        let verifier_params = to_canonical_bytes(&get_verifier_params().unwrap()).unwrap();
+
+        let (tokens, _) = matchmaking_tokens(&mut rng, &prover_params);
+        let encode = |params: &[u8]| {
+            let mut all = vec![Token::Bytes(params.to_vec())];
+            all.extend_from_slice(&tokens);
+            ethabi::encode(&all)
+        };
+
+        // raw canonical bytes, without an envelope
+        plonk_verify_matchmaking(&encode(&verifier_params)).unwrap();
+
+        let sealed = seal(
+            Curve::Bn254,
+            Circuit::Matchmaking,
+            Encoding::Canonical,
+            &verifier_params,
+        );
+        plonk_verify_matchmaking(&encode(&sealed)).unwrap();
+
+        // a truncated canonical encoding is not retried as bincode
+        assert!(matches!(
+            plonk_verify_matchmaking(&encode(&verifier_params[..verifier_params.len() - 1])),
+            Err(Error::Deserialize)
+        ));
+    }
+
+    #[test]
+    fn test_plonk_verify_matchmaking_with_seed() {
+        let mut rng = ChaChaRng::from_seed([0u8; 32]);
+        let prover_params = gen_prover_params().unwrap();
+        let verifier_params = bincode::serialize(&get_verifier_params().unwrap()).unwrap();
+
+        let (mut tokens, committed_seed) = matchmaking_tokens(&mut rng, &prover_params);
+        let commitment = tokens[2].clone();
+        let encode = |tokens: &[Token], expected: &Token| {
+            let mut all = vec![Token::Bytes(verifier_params.clone())];
+            all.extend_from_slice(tokens);
+            all.push(expected.clone());
+            ethabi::encode(&all)
+        };
+
+        // the commitment itself is not accepted as the seed
+        assert!(plonk_verify_matchmaking_with_seed(&encode(&tokens, &commitment)).is_err());
+
+        tokens[2] = Token::Bytes(committed_seed.into_bigint().to_bytes_be());
+        plonk_verify_matchmaking_with_seed(&encode(&tokens, &commitment)).unwrap();
+
+        // the seed must open the commitment the caller expects
+        let other = Token::Bytes((committed_seed + Fr::one()).into_bigint().to_bytes_be());
+        assert!(matches!(
+            plonk_verify_matchmaking_with_seed(&encode(&tokens, &other)),
+            Err(Error::VerifyFail)
+        ));
+    }
+
+    #[test]
+    fn test_plonk_verify_matchmaking_sized() {
+        let mut rng = ChaChaRng::from_seed([0u8; 32]);
+        let prover_params = gen_prover_params().unwrap();
+
+        let (mut tokens, _) = matchmaking_tokens(&mut rng, &prover_params);
+        let encode = |players: usize, tokens: &[Token]| {
+            let mut all = vec![Token::Uint(U256::from(players))];
+            all.extend_from_slice(tokens);
+            ethabi::encode(&all)
+        };
+
+        plonk_verify_matchmaking_sized(&encode(N, &tokens)).unwrap();
+
+        assert!(matches!(
+            plonk_verify_matchmaking_sized(&encode(N + 1, &tokens)),
+            Err(Error::UnsupportedSize)
+        ));
+
+        // the FFI entry reports an unsupported size as error code 7
+        for players in [0, N - 1, N + 1] {
+            let data = encode(players, &tokens);
+            let code = __precompile_verify_matchmaking_sized(data.as_ptr(), data.len());
+            assert_eq!(code, Error::UnsupportedSize.code());
+            assert_eq!(code, 7);
+        }
+        let data = encode(N, &tokens);
+        assert_eq!(
+            __precompile_verify_matchmaking_sized(data.as_ptr(), data.len()),
+            0
+        );
+
+        // gas grows with the player count, unsupported sizes only pay the base
+        let unsupported = encode(N + 1, &tokens);
+        assert_eq!(
+            __precompile_plonk_verify_matchmaking_sized_gas(data.as_ptr(), data.len()),
+            PLONL_VERIFY_BASE + 2 * N as u64 * PLONK_VERIFY_PER_INPUT
+        );
+        assert_eq!(
+            __precompile_plonk_verify_matchmaking_sized_gas(
+                unsupported.as_ptr(),
+                unsupported.len()
+            ),
+            PLONL_VERIFY_BASE
+        );
+
+        let mut inputs = tokens[0].clone().into_array().unwrap();
+        inputs.pop();
+        tokens[0] = Token::Array(inputs);
+        assert!(matches!(
+            plonk_verify_matchmaking_sized(&encode(N, &tokens)),
+            Err(Error::LengthMismatch)
+        ));
+    }
+
     #[derive(PartialEq, PartialOrd, Clone, Copy, Eq)]
     pub enum Value {
         Two,
@@ -429,4 +993,239 @@ mod tests {
         ]);
         plonk_verify_shuffle(&data).unwrap()
     }
+
+    #[test]
+    fn test_plonk_verify() {
+        let mut rng = ChaChaRng::from_seed([0u8; 32]);
+
+        // prove knowledge of a, b with a + b = c and a * b = d, where c and d are public
+        let a = Fr::rand(&mut rng);
+        let b = Fr::rand(&mut rng);
+        let mut cs = TurboCS::new();
+        let a_var = cs.new_variable(a);
+        let b_var = cs.new_variable(b);
+        let c_var = cs.add(a_var, b_var);
+        let d_var = cs.mul(a_var, b_var);
+        cs.prepare_pi_variable(c_var);
+        cs.prepare_pi_variable(d_var);
+        cs.pad();
+        let witness = cs.get_and_clear_witness();
+
+        let pcs = KZGCommitmentSchemeBN254::new(cs.size() + 2, &mut rng);
+        let prover_params = indexer(&cs, &pcs).unwrap();
+
+        let label = PLONK_TRANSCRIPT_LABELS[0];
+        let mut transcript = Transcript::new(label);
+        let proof = prover(
+            &mut rng,
+            &mut transcript,
+            &pcs,
+            &cs,
+            &prover_params,
+            &witness,
+        )
+        .unwrap();
+
+        let verifier_params = VerifierParams {
+            shrunk_vk: pcs.shrink_to_verifier_only().unwrap(),
+            shrunk_cs: cs.shrink_to_verifier_only(),
+            verifier_params: prover_params.verifier_params.clone(),
+        };
+        let canonical_params = to_canonical_bytes(&verifier_params).unwrap();
+        let verifier_params = bincode::serialize(&verifier_params).unwrap();
+        let proof = proof.to_bytes_be();
+
+        let encode_with_label =
+            |params: &[u8], public_inputs: &[Fr], proof: &[u8], label: &[u8]| {
+                let public_inputs = public_inputs
+                    .iter()
+                    .map(|v| Token::Uint(U256::from_big_endian(&v.into_bigint().to_bytes_be())))
+                    .collect();
+                ethabi::encode(&[
+                    Token::Bytes(params.to_vec()),
+                    Token::Array(public_inputs),
+                    Token::Bytes(proof.to_vec()),
+                    Token::Bytes(label.to_vec()),
+                ])
+            };
+        let encode = |params: &[u8], public_inputs: &[Fr], proof: &[u8]| {
+            encode_with_label(params, public_inputs, proof, label)
+        };
+
+        plonk_verify(&encode(&verifier_params, &[a + b, a * b], &proof)).unwrap();
+        assert!(plonk_verify(&encode(
+            &verifier_params,
+            &[a + b, a * b + Fr::one()],
+            &proof
+        ))
+        .is_err());
+
+        // the same params and proof wrapped in envelopes
+        let sealed_params = seal(
+            Curve::Bn254,
+            Circuit::Plonk,
+            Encoding::Bincode,
+            &verifier_params,
+        );
+        let sealed_proof = seal(Curve::Bn254, Circuit::Plonk, Encoding::BytesBe, &proof);
+        plonk_verify(&encode(&sealed_params, &[a + b, a * b], &sealed_proof)).unwrap();
+
+        let sealed_params = seal(
+            Curve::Bn254,
+            Circuit::Plonk,
+            Encoding::Canonical,
+            &canonical_params,
+        );
+        plonk_verify(&encode(&sealed_params, &[a + b, a * b], &sealed_proof)).unwrap();
+
+        plonk_verify(&encode(&canonical_params, &[a + b, a * b], &proof)).unwrap();
+
+        // params sealed for another curve are rejected
+        let other_curve = seal(
+            Curve::Bls12_381,
+            Circuit::Plonk,
+            Encoding::Canonical,
+            &canonical_params,
+        );
+        assert!(matches!(
+            plonk_verify(&encode(&other_curve, &[a + b, a * b], &sealed_proof)),
+            Err(Error::Deserialize)
+        ));
+
+        // the label must be a registered one
+        assert!(matches!(
+            plonk_verify(&encode_with_label(
+                &verifier_params,
+                &[a + b, a * b],
+                &proof,
+                b"other label"
+            )),
+            Err(Error::ParamsMismatch)
+        ));
+    }
+
+    #[test]
+    fn test_plonk_verify_shuffle_deck_size() {
+        let mut rng = ChaChaRng::from_seed([0u8; 32]);
+        let verifier_params =
+            bincode::serialize(&get_shuffle_verifier_params(N_CARDS).unwrap()).unwrap();
+
+        let deck = |n: usize, rng: &mut ChaChaRng| {
+            let mut ret = Vec::new();
+            for _ in 0..n {
+                let (x1, y1) = point_to_uncompress(&EdwardsProjective::rand(rng));
+                let (x2, y2) = point_to_uncompress(&EdwardsProjective::rand(rng));
+                ret.push(Token::Array(vec![
+                    Token::Bytes(x1),
+                    Token::Bytes(y1),
+                    Token::Bytes(x2),
+                    Token::Bytes(y2),
+                ]));
+            }
+            Token::Array(ret)
+        };
+
+        let mut verify = |num_inputs: usize, num_outputs: usize| {
+            let data = ethabi::encode(&[
+                Token::Bytes(verifier_params.clone()),
+                deck(num_inputs, &mut rng),
+                deck(num_outputs, &mut rng),
+                Token::Bytes(vec![]),
+            ]);
+            plonk_verify_shuffle(&data)
+        };
+
+        assert!(matches!(
+            verify(N_CARDS, N_CARDS - 1),
+            Err(Error::LengthMismatch)
+        ));
+        assert!(matches!(
+            verify(N_CARDS - 1, N_CARDS - 1),
+            Err(Error::UnsupportedSize)
+        ));
+        assert!(matches!(verify(20, 20), Err(Error::ParamsMismatch)));
+        // the right size reaches proof decoding
+        assert!(matches!(verify(N_CARDS, N_CARDS), Err(Error::Deserialize)));
+    }
+
+    #[test]
+    fn test_shuffle_bundled_params_deck_size() {
+        // zshuffle ships verifier params for these sizes
+        for n in [48, 52, 54] {
+            assert!(SHUFFLE_DECK_SIZES.contains(&n));
+            let verifier_params = get_shuffle_verifier_params(n).unwrap();
+            check_deck_size(&verifier_params, n, n).unwrap();
+        }
+    }
+
+    #[test]
+    fn test_plonk_verify_shuffle_invalid_card() {
+        let mut rng = ChaChaRng::from_seed([0u8; 32]);
+        let verifier_params =
+            bincode::serialize(&get_shuffle_verifier_params(N_CARDS).unwrap()).unwrap();
+
+        let deck = (0..N_CARDS)
+            .map(|_| {
+                let (x1, y1) = point_to_uncompress(&EdwardsProjective::rand(&mut rng));
+                let (x2, y2) = point_to_uncompress(&EdwardsProjective::rand(&mut rng));
+                vec![
+                    Token::Bytes(x1),
+                    Token::Bytes(y1),
+                    Token::Bytes(x2),
+                    Token::Bytes(y2),
+                ]
+            })
+            .collect::<Vec<_>>();
+
+        let verify = |inputs: &[Vec<Token>], outputs: &[Vec<Token>]| {
+            let data = ethabi::encode(&[
+                Token::Bytes(verifier_params.clone()),
+                Token::Array(inputs.iter().cloned().map(Token::Array).collect()),
+                Token::Array(outputs.iter().cloned().map(Token::Array).collect()),
+                Token::Bytes(vec![]),
+            ]);
+            plonk_verify_shuffle(&data)
+        };
+
+        // trailing element on input card 3
+        let mut inputs = deck.clone();
+        inputs[3].push(Token::Bytes(vec![0u8; 32]));
+        assert!(matches!(verify(&inputs, &deck), Err(Error::InvalidCard(3))));
+
+        // non-canonical coordinate on input card 10
+        let mut inputs = deck.clone();
+        inputs[10][0] = Token::Bytes(vec![0xff; 32]);
+        assert!(matches!(
+            verify(&inputs, &deck),
+            Err(Error::InvalidCard(10))
+        ));
+
+        // point off the curve on output card 7
+        let mut outputs = deck.clone();
+        outputs[7][3] = Token::Bytes(vec![1u8; 32]);
+        assert!(matches!(
+            verify(&deck, &outputs),
+            Err(Error::InvalidCard(i)) if i == N_CARDS + 7
+        ));
+
+        // the detailed entry returns the code and index, the plain one only the code
+        let data = ethabi::encode(&[
+            Token::Bytes(verifier_params.clone()),
+            Token::Array(deck.iter().cloned().map(Token::Array).collect()),
+            Token::Array(outputs.iter().cloned().map(Token::Array).collect()),
+            Token::Bytes(vec![]),
+        ]);
+        let mut ret = [0u8; 64];
+        let code =
+            __precompile_verify_shuffle_detailed(data.as_ptr(), data.len(), ret.as_mut_ptr());
+        assert_eq!(code, 9);
+        assert_eq!(
+            ethabi::decode(&[ParamType::Uint(256), ParamType::Uint(256)], &ret).unwrap(),
+            vec![
+                Token::Uint(U256::from(9)),
+                Token::Uint(U256::from(N_CARDS + 7))
+            ]
+        );
+        assert_eq!(__precompile_verify_shuffle(data.as_ptr(), data.len()), 9);
+    }
 }
@@ -0,0 +1,202 @@
+use alloc::boxed::Box;
+use alloc::vec::Vec;
+use ark_bn254::Fr;
+use ark_ff::{BigInteger, Field, PrimeField, Zero};
+use ark_std::panic;
+use core::slice;
+use ethabi::ParamType;
+
+use crate::{
+    anemoi::into_field_array,
+    poseidon_constants::{POSEIDON_C, POSEIDON_M},
+    utils, Error, Result,
+};
+
+pub const POSEIDON_BASE: u64 = 60;
+/// Gas per multiplication of the MDS mix, `t * t` of them in each round.
+pub const POSEIDON_PER_ROUND_MUL: u64 = 1;
+
+/// Smallest circomlib state width `t`, the inputs are `t - 1` elements.
+pub const POSEIDON_MIN_WIDTH: usize = 2;
+/// Largest circomlib state width `t`.
+pub const POSEIDON_MAX_WIDTH: usize = 17;
+
+const FULL_ROUNDS: usize = 8;
+/// Partial rounds of circomlib for `t = 2..=17`.
+const PARTIAL_ROUNDS: [usize; 16] = [
+    56, 57, 56, 60, 60, 63, 64, 63, 60, 66, 60, 65, 70, 60, 64, 68,
+];
+
+/// The Poseidon permutation of width `t` with the round layout and mixing of circomlib.
+fn permute(state: &mut [Fr]) {
+    let t = state.len();
+    let partial_rounds = PARTIAL_ROUNDS[t - POSEIDON_MIN_WIDTH];
+    let round_constants = POSEIDON_C[t - POSEIDON_MIN_WIDTH];
+    let mds = POSEIDON_M[t - POSEIDON_MIN_WIDTH];
+
+    let half = FULL_ROUNDS / 2;
+    for (r, constants) in round_constants.chunks_exact(t).enumerate() {
+        for (s, c) in state.iter_mut().zip(constants) {
+            *s += c;
+        }
+
+        if r < half || r >= half + partial_rounds {
+            state.iter_mut().for_each(|s| *s = s.pow([5]));
+        } else {
+            state[0] = state[0].pow([5]);
+        }
+
+        let mixed: Vec<Fr> = mds
+            .iter()
+            .map(|row| row.iter().zip(state.iter()).map(|(m, s)| *m * s).sum())
+            .collect();
+        state.copy_from_slice(&mixed);
+    }
+}
+
+/// circomlib `Poseidon(n)` of `inputs`, with `1 <= n <= 16`.
+pub fn poseidon_hash(inputs: &[Fr]) -> Result<Fr> {
+    let t = inputs.len() + 1;
+    if !(POSEIDON_MIN_WIDTH..=POSEIDON_MAX_WIDTH).contains(&t) {
+        return Err(Error::UnsupportedSize);
+    }
+
+    let mut state = Vec::with_capacity(t);
+    state.push(Fr::zero());
+    state.extend_from_slice(inputs);
+    permute(&mut state);
+
+    Ok(state[0])
+}
+
+#[no_mangle]
+#[allow(clippy::not_unsafe_ptr_arg_deref)]
+pub extern "C" fn __precompile_poseidon(
+    data_ptr: *const u8,
+    data_len: usize,
+    ret_val: *mut u8,
+) -> u8 {
+    let result = panic::catch_unwind(|| {
+        let data = unsafe { slice::from_raw_parts(data_ptr, data_len) };
+        let ret = unsafe { slice::from_raw_parts_mut(ret_val, 32) };
+        match eval_poseidon(data, ret) {
+            Ok(()) => 0,
+            Err(e) => e.code(),
+        }
+    });
+    if let Ok(code) = result {
+        code
+    } else {
+        Error::Unknown.code()
+    }
+}
+
+#[no_mangle]
+#[allow(clippy::not_unsafe_ptr_arg_deref)]
+pub extern "C" fn __precompile_poseidon_gas(data_ptr: *const u8, data_len: usize) -> u64 {
+    let data = unsafe { slice::from_raw_parts(data_ptr, data_len) };
+
+    // unsupported widths are rejected before any round runs
+    let t = utils::abi_array_len(data, 0).unwrap_or(0) + 1;
+    if !(POSEIDON_MIN_WIDTH..=POSEIDON_MAX_WIDTH).contains(&t) {
+        return POSEIDON_BASE;
+    }
+    let rounds = (FULL_ROUNDS + PARTIAL_ROUNDS[t - POSEIDON_MIN_WIDTH]) as u64;
+    let t = t as u64;
+
+    POSEIDON_BASE + rounds * t * t * POSEIDON_PER_ROUND_MUL
+}
+
+fn eval_poseidon(data: &[u8], ret: &mut [u8]) -> Result<()> {
+    let rs = ethabi::decode(
+        &[ParamType::Array(Box::new(ParamType::FixedBytes(32)))],
+        data,
+    )
+    .map_err(|_| Error::Deserialize)?;
+    let inputs = into_field_array(rs.first().cloned())?;
+
+    let res = poseidon_hash(&inputs)?;
+
+    ret.copy_from_slice(&res.into_bigint().to_bytes_be());
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ark_ff::{vec, MontFp};
+    use ethabi::Token;
+
+    #[test]
+    fn poseidon_circomlib_vectors() {
+        let inputs = (1..=16u64).map(Fr::from).collect::<Vec<_>>();
+
+        // circomlibjs poseidon([1]), poseidon([1, 2]), poseidon([1, 2, 3, 4]) and
+        // poseidon([1, 2, 3, 4, 5])
+        let vectors: [(usize, Fr); 4] = [
+            (
+                1,
+                MontFp!(
+                    "18586133768512220936620570745912940619677854269274689475585506675881198879027"
+                ),
+            ),
+            (
+                2,
+                MontFp!(
+                    "7853200120776062878684798364095072458815029376092732009249414926327459813530"
+                ),
+            ),
+            (
+                4,
+                MontFp!(
+                    "18821383157269793795438455681495246036402687001665670618754263018637548127333"
+                ),
+            ),
+            (
+                5,
+                MontFp!(
+                    "6183221330272524995739186171720101788151706631170188140075976616310159254464"
+                ),
+            ),
+        ];
+        for (n, expected) in vectors {
+            assert_eq!(poseidon_hash(&inputs[..n]).unwrap(), expected);
+        }
+
+        // the widest circuit, Poseidon(16) of 1..=16
+        let expected: Fr =
+            MontFp!("9989051620750914585850546081941653841776809718687451684622678807385399211877");
+        assert_eq!(poseidon_hash(&inputs).unwrap(), expected);
+
+        let data = ethabi::encode(&[Token::Array(vec![
+            Token::FixedBytes(inputs[0].into_bigint().to_bytes_be()),
+            Token::FixedBytes(inputs[1].into_bigint().to_bytes_be()),
+        ])]);
+        let mut ret = vec![0u8; 32];
+        eval_poseidon(&data, &mut ret).unwrap();
+        assert_eq!(ret, vectors[1].1.into_bigint().to_bytes_be());
+
+        // t = 3 runs 8 + 57 rounds of 9 MDS multiplications
+        let gas = __precompile_poseidon_gas(data.as_ptr(), data.len());
+        assert_eq!(gas, POSEIDON_BASE + 65 * 9 * POSEIDON_PER_ROUND_MUL);
+
+        assert!(matches!(poseidon_hash(&[]), Err(Error::UnsupportedSize)));
+        assert!(matches!(
+            poseidon_hash(&[Fr::from(1u64); POSEIDON_MAX_WIDTH]),
+            Err(Error::UnsupportedSize)
+        ));
+    }
+
+    #[test]
+    fn poseidon_constants_shape() {
+        for t in POSEIDON_MIN_WIDTH..=POSEIDON_MAX_WIDTH {
+            let rounds = FULL_ROUNDS + PARTIAL_ROUNDS[t - POSEIDON_MIN_WIDTH];
+            assert_eq!(POSEIDON_C[t - POSEIDON_MIN_WIDTH].len(), rounds * t);
+            assert_eq!(POSEIDON_M[t - POSEIDON_MIN_WIDTH].len(), t);
+            assert!(POSEIDON_M[t - POSEIDON_MIN_WIDTH]
+                .iter()
+                .all(|row| row.len() == t));
+        }
+    }
+}
@@ -1,4 +1,5 @@
 use alloc::vec::Vec;
+use ark_ff::{BigInteger, PrimeField};
 use ethabi::{Address, Token};
 use primitive_types::U256;
 
@@ -95,6 +96,22 @@ pub fn into_bytes_array(tk: Option<Token>) -> Option<Vec<Vec<u8>>> {
     Some(res)
 }
 
+/// Read the length of the dynamic array whose offset is the `index`-th head word of `data`.
+pub fn abi_array_len(data: &[u8], index: usize) -> Option<usize> {
+    let offset = U256::from_big_endian(data.get(index * 32..(index + 1) * 32)?);
+    if offset > U256::from(data.len()) {
+        return None;
+    }
+    let offset = offset.as_usize();
+
+    let len = U256::from_big_endian(data.get(offset..offset + 32)?);
+    if len > U256::from(data.len()) {
+        return None;
+    }
+
+    Some(len.as_usize())
+}
+
 pub fn join_bytes32(byte32s: &[[u8; 32]]) -> Vec<u8> {
     let mut v = Vec::with_capacity(byte32s.len() * 32);
 
@@ -121,3 +138,55 @@ pub fn split_bytes32(bytes32: &[u8]) -> Result<Vec<&[u8; 32]>> {
 
     Ok(res)
 }
+
+/// Map `f` over `items`, on the rayon thread pool when the `parallel` feature is enabled.
+///
+/// The output order always matches `items`, so results are identical in both builds.
+pub fn par_map<T, U, F>(items: &[T], f: F) -> Vec<U>
+where
+    T: Sync,
+    U: Send,
+    F: Fn(&T) -> U + Sync + Send,
+{
+    #[cfg(feature = "parallel")]
+    {
+        use rayon::prelude::*;
+        items.par_iter().map(f).collect()
+    }
+    #[cfg(not(feature = "parallel"))]
+    {
+        items.iter().map(f).collect()
+    }
+}
+
+/// Fallible [`par_map`], stopping at the first error in both builds.
+///
+/// Sequentially this is the first error in `items` order; on the thread pool it is
+/// whichever failing item rayon reaches first.
+pub fn par_try_map<T, U, F>(items: &[T], f: F) -> Result<Vec<U>>
+where
+    T: Sync,
+    U: Send,
+    F: Fn(&T) -> Result<U> + Sync + Send,
+{
+    #[cfg(feature = "parallel")]
+    {
+        use rayon::prelude::*;
+        items.par_iter().map(f).collect()
+    }
+    #[cfg(not(feature = "parallel"))]
+    {
+        items.iter().map(f).collect()
+    }
+}
+
+/// Decode a canonical field element, rejecting values not below the modulus.
+pub fn into_field<F: PrimeField>(v: U256) -> Result<F> {
+    let mut tmp_bytes = [0u8; 32];
+    v.to_big_endian(&mut tmp_bytes);
+    let f = F::from_be_bytes_mod_order(&tmp_bytes);
+    if f.into_bigint().to_bytes_be() != tmp_bytes {
+        return Err(Error::Deserialize);
+    }
+    Ok(f)
+}